@@ -1,11 +1,10 @@
 
 extern crate int_range_check;
 
-use std::fmt::Display;
-use std::num::Int;
-
 use int_range_check::uncovered_and_overlapped;
+use int_range_check::Int;
 use int_range_check::IntRange;
+use int_range_check::RangeList;
 use int_range_check::IntRange::*;
 
 fn main() {
@@ -15,10 +14,10 @@ fn main() {
     example_driver("Example 2b", vec![Bound(0u8, 5), Bound(250, 255)]);
 }
 
-fn example_driver<T: Display+Int>(title: &str, ranges: Vec<IntRange<T>>) {
+fn example_driver<T: Int>(title: &str, ranges: Vec<IntRange<T>>) {
     let (uncovered, overlapped) =
         uncovered_and_overlapped(&ranges);
-    println!("{} input ranges: {}", title, ranges);
-    println!("{} uncovered ranges: {}", title, uncovered);
-    println!("{} overlapping ranges: {}", title, overlapped);
+    println!("{} input ranges: {}", title, RangeList::new(&ranges));
+    println!("{} uncovered ranges: {}", title, RangeList::new(&uncovered));
+    println!("{} overlapping ranges: {}", title, RangeList::new(&overlapped));
 }
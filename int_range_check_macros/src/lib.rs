@@ -0,0 +1,89 @@
+//! Procedural macros for `int_range_check`.
+//!
+//! These are re-exported by `int_range_check` when its `derive` feature is
+//! enabled, and should be used from there.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Error, Fields, Ident, parse_macro_input};
+
+// The types allowed in `#[repr]` that `int_range_check` can check.
+const INT_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize",
+    "i8", "i16", "i32", "i64", "i128", "isize",
+];
+
+/// Derives `RangeDomain` for a fieldless enum with an integer `#[repr]`,
+/// such as `#[repr(u8)]`.
+///
+/// The discriminants may be explicit or implicit; they are read by the
+/// compiler, so any constant expression is allowed.
+#[proc_macro_derive(RangeDomain)]
+pub fn derive_range_domain(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    range_domain(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn range_domain(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => return Err(Error::new_spanned(
+            &input.ident, "`RangeDomain` can only be derived for enums")),
+    };
+    let repr = repr_type(input)?;
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant, "`RangeDomain` requires variants without fields"));
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+    let variants: Vec<&Ident> =
+        data.variants.iter().map(|variant| &variant.ident).collect();
+    let names = variants.iter().map(|ident| ident.unraw().to_string());
+    Ok(quote! {
+        impl #impl_generics ::int_range_check::RangeDomain
+            for #name #ty_generics #where_clause {
+            type Repr = #repr;
+            const VARIANTS: &'static [(&'static str, #repr)] =
+                &[#((#names, Self::#variants as #repr)),*];
+            fn discriminant(&self) -> #repr {
+                match *self {
+                    #(Self::#variants => Self::#variants as #repr,)*
+                }
+            }
+        }
+    })
+}
+
+// Find the integer type in the `#[repr]` attributes of the input.
+fn repr_type(input: &DeriveInput) -> syn::Result<Ident> {
+    let mut repr = None;
+    for attr in input.attrs.iter() {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            match meta.path.get_ident() {
+                Some(ident) if INT_TYPES.contains(
+                    &ident.to_string().as_str()) => repr = Some(ident.clone()),
+                // Skip other representation hints, like `align(4)`.
+                _ => if meta.input.peek(syn::token::Paren) {
+                    let hint;
+                    syn::parenthesized!(hint in meta.input);
+                    hint.parse::<TokenStream2>()?;
+                },
+            }
+            Ok(())
+        })?;
+    }
+    repr.ok_or_else(|| Error::new_spanned(
+        &input.ident,
+        "`RangeDomain` requires an integer `#[repr]`, such as `#[repr(u8)]`"))
+}
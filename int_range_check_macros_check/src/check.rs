@@ -0,0 +1,242 @@
+//! Implementation of `check_ranges!`.
+
+use std::ops::Bound;
+
+use int_range_check::{Int, Notation, RangeCheck, RangeList, RangeStyle};
+use int_range_check::{Validation, overlap_provenance, validate_ranges};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Error, Expr, ExprLit, ExprUnary, Ident, Lit, RangeLimits, Token};
+use syn::{UnOp, bracketed};
+
+pub struct CheckInput {
+    ty: Ident,
+    ranges: Vec<Expr>,
+    require_exhaustive: bool,
+    require_disjoint: bool,
+}
+
+impl Parse for CheckInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let list;
+        bracketed!(list in input);
+        let ranges = Punctuated::<Expr, Token![,]>::parse_terminated(&list)?
+            .into_iter().collect();
+        let mut require_exhaustive = false;
+        let mut require_disjoint = false;
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let option: Ident = input.parse()?;
+            if option == "require_exhaustive" {
+                require_exhaustive = true;
+            } else if option == "require_disjoint" {
+                require_disjoint = true;
+            } else {
+                return Err(Error::new(option.span(),
+                                      "expected `require_exhaustive` or \
+                                       `require_disjoint`"));
+            }
+        }
+        // With no options, require both.
+        if !require_exhaustive && !require_disjoint {
+            require_exhaustive = true;
+            require_disjoint = true;
+        }
+        Ok(CheckInput{ty, ranges, require_exhaustive, require_disjoint})
+    }
+}
+
+macro_rules! dispatch {
+    ($input:expr, $($t:ident),*) => {
+        match $input.ty.to_string().as_str() {
+            $(stringify!($t) => check::<$t>($input),)*
+            _ => Err(Error::new(
+                $input.ty.span(),
+                "expected a primitive integer type or `char`")),
+        }
+    }
+}
+
+pub fn check_ranges(input: &CheckInput) -> syn::Result<TokenStream2> {
+    dispatch!(input, u8, u16, u32, u64, u128, usize,
+              i8, i16, i32, i64, i128, isize, char)
+}
+
+fn check<T: Int>(input: &CheckInput) -> syn::Result<TokenStream2> {
+    let bounds = input.ranges.iter()
+        .map(range_bounds::<T>)
+        .collect::<syn::Result<Vec<_>>>()?;
+    let mut errors: Vec<Error> = Vec::new();
+    let valid = match validate_ranges(bounds.iter().cloned(),
+                                      Validation::Strict) {
+        Ok(valid) => valid,
+        Err(error) => {
+            for invalid in error.invalid {
+                errors.push(Error::new_spanned(&input.ranges[invalid.index],
+                                               invalid));
+            }
+            Vec::new()
+        },
+    };
+    if errors.is_empty() {
+        let style = RangeStyle::new(Notation::Pattern);
+        let check = RangeCheck::new(&valid);
+        if input.require_exhaustive && !check.is_exhaustive() {
+            let uncovered: Vec<_> = check.uncovered().collect();
+            errors.push(Error::new(Span::call_site(), format!(
+                "ranges of `{}` are not exhaustive; uncovered: {}",
+                T::NAME, RangeList::new(&uncovered).style(style))));
+        }
+        if input.require_disjoint {
+            for overlap in overlap_provenance(bounds.iter().cloned()) {
+                let sources: Vec<String> = overlap.sources.iter()
+                    .map(|i| i.to_string()).collect();
+                let last = *overlap.sources.last().unwrap();
+                errors.push(Error::new_spanned(&input.ranges[last], format!(
+                    "ranges {} overlap on {}", sources.join(", "),
+                    overlap.range.styled(style))));
+            }
+        }
+    }
+    match errors.into_iter().reduce(|mut all, error| {
+        all.combine(error);
+        all
+    }) {
+        Some(error) => Err(error),
+        None => Ok(quote!(const _: () = ();)),
+    }
+}
+
+// Read a range expression, such as `0..=9`, `..10`, `5` or `_`.
+fn range_bounds<T: Int>(expr: &Expr) -> syn::Result<(Bound<T>, Bound<T>)> {
+    match *expr {
+        Expr::Range(ref range) => {
+            let start = match range.start {
+                Some(ref start) => Bound::Included(value(start)?),
+                None => Bound::Unbounded,
+            };
+            let end = match (&range.end, range.limits) {
+                (Some(end), RangeLimits::Closed(_)) =>
+                    Bound::Included(value(end)?),
+                (Some(end), RangeLimits::HalfOpen(_)) =>
+                    Bound::Excluded(value(end)?),
+                (None, _) => Bound::Unbounded,
+            };
+            Ok((start, end))
+        },
+        Expr::Infer(_) => Ok((Bound::Unbounded, Bound::Unbounded)),
+        _ => {
+            let x = value(expr)?;
+            Ok((Bound::Included(x), Bound::Included(x)))
+        },
+    }
+}
+
+fn value<T: Int>(expr: &Expr) -> syn::Result<T> {
+    let (negative, magnitude) = sign_magnitude::<T>(expr)?;
+    T::from_sign_magnitude(negative, magnitude).ok_or_else(|| {
+        Error::new_spanned(expr,
+                           format!("value out of range for `{}`", T::NAME))
+    })
+}
+
+// Read a constant expression as a sign (true if negative) and a magnitude.
+fn sign_magnitude<T: Int>(expr: &Expr) -> syn::Result<(bool, u128)> {
+    match *expr {
+        Expr::Lit(ExprLit{lit: Lit::Int(ref lit), ..}) => {
+            if !lit.suffix().is_empty() && lit.suffix() != T::NAME {
+                return Err(Error::new_spanned(
+                    lit, format!("expected a `{}` literal", T::NAME)));
+            }
+            Ok((false, lit.base10_parse()?))
+        },
+        Expr::Lit(ExprLit{lit: Lit::Char(ref lit), ..}) if T::NAME == "char" =>
+            Ok((false, lit.value() as u128)),
+        Expr::Lit(ExprLit{lit: Lit::Byte(ref lit), ..}) if T::NAME == "u8" =>
+            Ok((false, lit.value() as u128)),
+        Expr::Unary(ExprUnary{op: UnOp::Neg(_), expr: ref inner, ..}) => {
+            let (negative, magnitude) = sign_magnitude::<T>(inner)?;
+            Ok((!negative, magnitude))
+        },
+        Expr::Paren(ref paren) => sign_magnitude::<T>(&paren.expr),
+        Expr::Group(ref group) => sign_magnitude::<T>(&group.expr),
+        Expr::Path(ref path) if path.qself.is_none() => {
+            let segments: Vec<String> = path.path.segments.iter()
+                .map(|segment| segment.ident.to_string())
+                .collect();
+            let value = match segments.iter().map(|x| x.as_str())
+                .collect::<Vec<_>>()[..] {
+                ["MIN"] => T::min_value(),
+                ["MAX"] => T::max_value(),
+                [ty, "MIN"] if ty == T::NAME => T::min_value(),
+                [ty, "MAX"] if ty == T::NAME => T::max_value(),
+                _ => return Err(Error::new_spanned(path, format!(
+                    "expected `{0}::MIN` or `{0}::MAX`", T::NAME))),
+            };
+            Ok(value.to_sign_magnitude())
+        },
+        _ => Err(Error::new_spanned(expr, format!(
+            "expected a `{}` literal or constant", T::NAME))),
+    }
+}
+
+#[cfg(test)]
+mod check_tests {
+    use quote::quote;
+    use super::{CheckInput, check_ranges};
+    // Expand the macro, returning the error messages, if any.
+    fn errors(input: proc_macro2::TokenStream) -> Vec<String> {
+        let input: CheckInput = syn::parse2(input).unwrap();
+        match check_ranges(&input) {
+            Ok(_) => Vec::new(),
+            Err(error) => error.into_iter().map(|e| e.to_string()).collect(),
+        }
+    }
+    #[test]
+    fn exhaustive_and_disjoint_passes() {
+        assert!(errors(quote!(u8, [0..=9, 10..100, 100..])).is_empty());
+        assert!(errors(quote!(i8, [i8::MIN..0, 0, 1..=MAX])).is_empty());
+        assert!(errors(quote!(char, ['\0'..='a', 'b'..])).is_empty());
+        assert!(errors(quote!(u8, [_])).is_empty());
+    }
+    #[test]
+    fn uncovered_ranges_listed() {
+        assert_eq!(errors(quote!(i16, [-5..=5, 10..=20])), vec![
+            "ranges of `i16` are not exhaustive; uncovered: \
+             i16::MIN..=-6 | 6..=9 | 21..=i16::MAX"]);
+        assert!(errors(quote!(i16, [-5..=5, 10..=20], require_disjoint))
+                .is_empty());
+    }
+    #[test]
+    fn overlaps_reported() {
+        assert_eq!(errors(quote!(u8, [..=10, 5..=20, 15.., 3])), vec![
+            "ranges 0, 3 overlap on 3",
+            "ranges 0, 1 overlap on 5..=10",
+            "ranges 1, 2 overlap on 15..=20",
+            ]);
+        assert!(errors(quote!(u8, [..=10, 5.., 3], require_exhaustive))
+                .is_empty());
+    }
+    #[test]
+    fn invalid_input_rejected() {
+        assert_eq!(errors(quote!(u8, [5..=1, 0..])),
+                   vec!["range 0 (5..=1) has its bounds reversed"]);
+        assert_eq!(errors(quote!(u8, [0..=256])),
+                   vec!["value out of range for `u8`"]);
+        assert_eq!(errors(quote!(u8, [0i8..])),
+                   vec!["expected a `u8` literal"]);
+        assert_eq!(errors(quote!(u8, [i8::MIN..])),
+                   vec!["expected `u8::MIN` or `u8::MAX`"]);
+        assert_eq!(errors(quote!(f32, [..])),
+                   vec!["expected a primitive integer type or `char`"]);
+        assert!(syn::parse2::<CheckInput>(quote!(u8, [..], disjoint))
+                .is_err());
+    }
+}
@@ -0,0 +1,72 @@
+//! The `check_ranges!` macro, which checks lists of integer ranges at compile
+//! time.
+//!
+//! The checks run inside the macro, using `int_range_check` itself, so this
+//! macro lives apart from `int_range_check_macros`, which `int_range_check`
+//! depends on for its `derive` feature.
+
+use proc_macro::TokenStream;
+use syn::{Error, parse_macro_input};
+
+mod check;
+
+/// Checks a list of ranges at compile time, failing compilation if they
+/// leave values uncovered or overlap.
+///
+/// The first argument is the integer type (or `char`), and the second is a
+/// bracketed list of ranges, as in `check_ranges!(u8, [0..=9, 10..])`. Range
+/// bounds may be literals, possibly negated, or the constants `MIN` and `MAX`
+/// written as `u8::MAX` or just `MAX`. A bare value matches just itself, and
+/// `_` matches everything.
+///
+/// By default, the ranges must be exhaustive and disjoint. To check just one
+/// of those, add `require_exhaustive` or `require_disjoint` after the list.
+/// Every uncovered range is listed in one error, while each overlap is
+/// reported at the later of the overlapping ranges.
+///
+/// On success, the macro expands to an unnamed constant, so it may be used
+/// wherever an item may appear.
+///
+/// ```
+/// use int_range_check_macros_check::check_ranges;
+///
+/// check_ranges!(u8, [0..=9, 10..100, 100..]);
+/// check_ranges!(i8, [MIN..0, 1..], require_disjoint);
+/// ```
+///
+/// Ranges that leave a value uncovered are rejected:
+///
+/// ```compile_fail
+/// use int_range_check_macros_check::check_ranges;
+///
+/// check_ranges!(u8, [0..=9, 11..]);
+/// ```
+///
+/// So are overlapping ranges:
+///
+/// ```compile_fail
+/// use int_range_check_macros_check::check_ranges;
+///
+/// check_ranges!(i16, [..=0, 0..], require_disjoint);
+/// ```
+///
+/// And so are reversed ranges and values out of range for the type:
+///
+/// ```compile_fail
+/// use int_range_check_macros_check::check_ranges;
+///
+/// check_ranges!(u8, [9..=0, 0..]);
+/// ```
+///
+/// ```compile_fail
+/// use int_range_check_macros_check::check_ranges;
+///
+/// check_ranges!(u8, [0..=256]);
+/// ```
+#[proc_macro]
+pub fn check_ranges(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as check::CheckInput);
+    check::check_ranges(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,18 @@
+//! Invocations of `check_ranges!` that must compile. Those that must not are
+//! `compile_fail` examples in the documentation of the macro.
+
+use int_range_check_macros_check::check_ranges;
+
+check_ranges!(u8, [0..=9, 10..100, 100..]);
+check_ranges!(i8, [i8::MIN..0, 0, 1..=MAX]);
+check_ranges!(char, ['\0'..='a', 'b'..]);
+check_ranges!(u32, [_]);
+check_ranges!(i16, [-5..=5, 10..=20], require_disjoint);
+check_ranges!(u8, [..=10, 5.., 3], require_exhaustive);
+check_ranges!(u128, [..=0xFF, 0x100..=(u128::MAX)],);
+
+#[test]
+fn checks_inside_functions() {
+    check_ranges!(i64, [..0, 0, 1..]);
+    check_ranges!(usize, [0..1, 1..], require_exhaustive, require_disjoint);
+}
@@ -0,0 +1,237 @@
+//! Ordered analysis of match arms, where each value goes to the first arm
+//! that matches it.
+
+use std::borrow::Borrow;
+use std::ops::RangeBounds;
+
+use super::{Int, IntRange, RangeSet};
+
+/// Whether an arm applies to every value of its pattern.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Guard {
+    /// The arm has no guard, so it takes every value that reaches it.
+    Unguarded,
+    /// The arm has an `if` guard, so values that reach it may fall through
+    /// to later arms.
+    Guarded,
+}
+
+/// How much of an arm's pattern can be reached.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Reachability {
+    /// No earlier arm matches any value of the pattern.
+    Reachable,
+    /// Earlier arms match some, but not all, values of the pattern.
+    PartiallyReachable,
+    /// Earlier arms match every value of the pattern, or the pattern is
+    /// empty. The arm is dead code.
+    Unreachable,
+}
+
+/// The analysis of a single arm.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArmReport<T: Int> {
+    /// The values of the pattern that can reach this arm.
+    pub effective: RangeSet<T>,
+    /// The values of the pattern that earlier unguarded arms always take.
+    pub shadowed: RangeSet<T>,
+    pub reachability: Reachability,
+    pub guard: Guard,
+}
+
+/// Analysis of an ordered list of match arms, with first-match semantics.
+///
+/// Unlike `uncovered_and_overlapped`, overlap between arms is not reported
+/// as such, since it is usually intended; what matters is which values each
+/// arm actually receives.
+///
+/// As in rustc, guarded arms never shadow later arms and never make a match
+/// exhaustive. The values they match are reported as `conditional`, which
+/// together with `covered()` and `uncovered` divides the values of the type
+/// into three disjoint sets.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchAnalysis<T: Int> {
+    /// One report for each arm, in order.
+    pub arms: Vec<ArmReport<T>>,
+    /// The values that only guarded arms match, and so are covered under
+    /// some guards but not others.
+    pub conditional: RangeSet<T>,
+    /// The values that no arm matches.
+    pub uncovered: RangeSet<T>,
+}
+
+impl<T: Int> MatchAnalysis<T> {
+    /// Analyzes arms that each match one range. The input is accepted in the
+    /// same forms as for `uncovered_and_overlapped`; an empty range is an arm
+    /// that matches nothing.
+    pub fn new<I>(ranges: I) -> Self
+        where I: IntoIterator, I::Item: RangeBounds<T> {
+        MatchAnalysis::with_guards(
+            ranges.into_iter().map(|range| (range, Guard::Unguarded)))
+    }
+    /// Like `new`, but for arms that may have guards.
+    pub fn with_guards<R, I>(arms: I) -> Self
+        where R: RangeBounds<T>, I: IntoIterator<Item=(R, Guard)> {
+        MatchAnalysis::from_guarded_patterns(arms.into_iter().map(
+            |(range, guard)| {
+                let pattern = match IntRange::from_range_bounds(&range) {
+                    Some(int_range) => RangeSet::from(int_range),
+                    None => RangeSet::new(),
+                };
+                (pattern, guard)
+            }))
+    }
+    /// Analyzes arms whose patterns are sets of values, as for or-patterns
+    /// like `1 | 5..=9`.
+    pub fn from_patterns<I>(patterns: I) -> Self
+        where I: IntoIterator, I::Item: Borrow<RangeSet<T>> {
+        MatchAnalysis::from_guarded_patterns(
+            patterns.into_iter().map(|pattern| (pattern, Guard::Unguarded)))
+    }
+    /// Like `from_patterns`, but for arms that may have guards.
+    pub fn from_guarded_patterns<P, I>(patterns: I) -> Self
+        where P: Borrow<RangeSet<T>>, I: IntoIterator<Item=(P, Guard)> {
+        // Values that an unguarded arm is sure to take, and values that some
+        // guarded arm might take.
+        let mut matched = RangeSet::new();
+        let mut maybe_matched = RangeSet::new();
+        let mut arms = Vec::new();
+        for (pattern, guard) in patterns {
+            let pattern = pattern.borrow();
+            let effective = pattern - &matched;
+            let shadowed = pattern & &matched;
+            let reachability = if effective.is_empty() {
+                Reachability::Unreachable
+            } else if shadowed.is_empty() {
+                Reachability::Reachable
+            } else {
+                Reachability::PartiallyReachable
+            };
+            match guard {
+                Guard::Unguarded => matched = matched | &effective,
+                Guard::Guarded => maybe_matched = maybe_matched | &effective,
+            }
+            arms.push(ArmReport{effective, shadowed, reachability, guard});
+        }
+        MatchAnalysis{
+            arms,
+            conditional: &maybe_matched - &matched,
+            uncovered: !(matched | maybe_matched),
+        }
+    }
+    /// Returns the values that some unguarded arm is sure to match.
+    pub fn covered(&self) -> RangeSet<T> {
+        !(&self.conditional | &self.uncovered)
+    }
+    /// Returns true if every value is matched by some unguarded arm.
+    pub fn is_exhaustive(&self) -> bool {
+        self.uncovered.is_empty() && self.conditional.is_empty()
+    }
+    /// Returns the indices of the arms that can never be reached.
+    pub fn unreachable_arms(&self) -> Vec<usize> {
+        self.arms_with(Reachability::Unreachable)
+    }
+    /// Returns the indices of the arms that are partially shadowed by
+    /// earlier arms.
+    pub fn partially_reachable_arms(&self) -> Vec<usize> {
+        self.arms_with(Reachability::PartiallyReachable)
+    }
+    fn arms_with(&self, reachability: Reachability) -> Vec<usize> {
+        self.arms.iter().enumerate()
+            .filter(|&(_, arm)| arm.reachability == reachability)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod arms_tests {
+    use super::{Guard, MatchAnalysis, Reachability};
+    use super::super::{IntRange, RangeSet};
+    #[test]
+    fn first_match_wins() {
+        let analysis = MatchAnalysis::new([
+            IntRange::Bound(0u8, 10),
+            IntRange::Bound(5u8, 20),
+            IntRange::Full,
+            ]);
+        assert_eq!(analysis.arms[1].effective.ranges(),
+                   vec![IntRange::Bound(11u8, 20)]);
+        assert_eq!(analysis.arms[1].shadowed.ranges(),
+                   vec![IntRange::Bound(5u8, 10)]);
+        assert_eq!(analysis.arms[2].effective.ranges(),
+                   vec![IntRange::From(21u8)]);
+        assert_eq!(analysis.partially_reachable_arms(), vec![1, 2]);
+        assert!(analysis.unreachable_arms().is_empty());
+        assert!(analysis.is_exhaustive());
+    }
+    #[test]
+    fn shadowed_arm_is_unreachable() {
+        let analysis =
+            MatchAnalysis::new([i32::MIN..=0, -5..=-1, 1..=9, -3..=3]);
+        assert_eq!(analysis.unreachable_arms(), vec![1, 3]);
+        assert_eq!(analysis.arms[3].effective, RangeSet::new());
+        assert_eq!(analysis.uncovered.ranges(), vec![IntRange::From(10i32)]);
+        assert!(!analysis.is_exhaustive());
+    }
+    #[test]
+    fn empty_arm_is_unreachable() {
+        let analysis = MatchAnalysis::new([IntRange::Bound(5u8, 1)]);
+        assert_eq!(analysis.arms[0].reachability, Reachability::Unreachable);
+        assert_eq!(analysis.uncovered, RangeSet::full());
+    }
+    #[test]
+    fn or_patterns() {
+        let letters: RangeSet<u8> = [IntRange::Bound(b'a', b'z'),
+                                     IntRange::Bound(b'A', b'Z')]
+            .into_iter().collect();
+        let vowels: RangeSet<u8> = b"aeiou".iter()
+            .map(|&c| IntRange::Bound(c, c)).collect();
+        let analysis = MatchAnalysis::from_patterns([&vowels, &letters]);
+        assert_eq!(analysis.arms[0].reachability, Reachability::Reachable);
+        assert_eq!(analysis.arms[1].reachability,
+                   Reachability::PartiallyReachable);
+        assert_eq!(analysis.arms[1].shadowed, vowels);
+        assert_eq!(analysis.uncovered, !&letters);
+    }
+    #[test]
+    fn guarded_arms_are_conditional() {
+        let analysis = MatchAnalysis::with_guards([
+            (IntRange::Bound(0u8, 10), Guard::Guarded),
+            (IntRange::Bound(5u8, 20), Guard::Unguarded),
+            (IntRange::Bound(30u8, 40), Guard::Guarded),
+            (IntRange::From(35u8), Guard::Unguarded),
+            (IntRange::Bound(50u8, 60), Guard::Guarded),
+            ]);
+        assert_eq!(analysis.covered().ranges(), vec![IntRange::Bound(5u8, 20),
+                                                     IntRange::From(35)]);
+        assert_eq!(analysis.conditional.ranges(),
+                   vec![IntRange::To(4u8), IntRange::Bound(30, 34)]);
+        assert_eq!(analysis.uncovered.ranges(),
+                   vec![IntRange::Bound(21u8, 29)]);
+        assert!(!analysis.is_exhaustive());
+        // A guarded arm does not shadow later arms.
+        assert_eq!(analysis.arms[1].reachability, Reachability::Reachable);
+        assert_eq!(analysis.arms[3].reachability, Reachability::Reachable);
+        assert_eq!(analysis.unreachable_arms(), vec![4]);
+    }
+    #[test]
+    fn guarded_catch_all_is_not_exhaustive() {
+        let analysis = MatchAnalysis::with_guards([
+            (IntRange::<i16>::Full, Guard::Guarded),
+            ]);
+        assert_eq!(analysis.conditional, RangeSet::full());
+        assert!(analysis.uncovered.is_empty());
+        assert!(!analysis.is_exhaustive());
+        let analysis = MatchAnalysis::with_guards([
+            (IntRange::<i16>::Full, Guard::Guarded),
+            (IntRange::Full, Guard::Unguarded),
+            ]);
+        assert!(analysis.is_exhaustive());
+        assert_eq!(analysis.covered(), RangeSet::full());
+    }
+}
@@ -0,0 +1,205 @@
+//! Checks lists of ranges for exhaustiveness and overlap.
+//!
+//! Usage: `int_range_check TYPE [OPTIONS] [FILE...]`
+//!
+//! `TYPE` is the integer type of the ranges, such as `u8`, `i32` or `char`.
+//! Each file holds one list of ranges, written in any notation that the crate
+//! parses, and may spread it over several lines. With no files, or a file
+//! named `-`, the list is read from standard input.
+//!
+//! Options:
+//!
+//!  * `--require-exhaustive`: exit with status 1 unless every list covers
+//!    every value of the type.
+//!
+//!  * `--require-disjoint`: exit with status 1 unless no list covers a value
+//!    more than once.
+//!
+//!  * `--json`: print a JSON array with one report per list, instead of
+//!    text.
+//!
+//! The exit status is 2 if the arguments are wrong, or if a file cannot be
+//! read, parsed or validated. Such a file is reported on standard error, and
+//! the other files are still checked and reported as usual.
+
+use std::io::{self, Read};
+use std::{env, fs, process};
+
+use int_range_check::{CheckReport, Int, IntRange, Notation, RangeList};
+use int_range_check::{RangeStyle, Validation, parse_ranges, validate_ranges};
+use serde::Serialize;
+
+const USAGE: &str = "usage: int_range_check TYPE [--require-exhaustive] \
+                     [--require-disjoint] [--json] [FILE...]";
+
+struct Options {
+    require_exhaustive: bool,
+    require_disjoint: bool,
+    json: bool,
+    paths: Vec<String>,
+}
+
+// The JSON report on one list.
+#[derive(Serialize)]
+struct Output<'a, T: Int + Serialize> {
+    input: &'a str,
+    #[serde(rename = "type")]
+    ty: &'static str,
+    exhaustive: bool,
+    disjoint: bool,
+    #[serde(flatten)]
+    report: CheckReport<T>,
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let ty = args.next().unwrap_or_else(|| usage());
+    let mut options = Options{
+        require_exhaustive: false,
+        require_disjoint: false,
+        json: false,
+        paths: Vec::new(),
+    };
+    for arg in args {
+        match arg.as_str() {
+            "--require-exhaustive" => options.require_exhaustive = true,
+            "--require-disjoint" => options.require_disjoint = true,
+            "--json" => options.json = true,
+            _ if arg.starts_with("--") => usage(),
+            _ => options.paths.push(arg),
+        }
+    }
+    if options.paths.is_empty() {
+        options.paths.push("-".to_string());
+    }
+    macro_rules! dispatch {
+        ($($t:ident),*) => {
+            match ty.as_str() {
+                $(stringify!($t) => run::<$t>(&options),)*
+                _ => usage(),
+            }
+        }
+    }
+    let status = dispatch!(u8, u16, u32, u64, u128, usize,
+                           i8, i16, i32, i64, i128, isize, char);
+    process::exit(status);
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+// Check every input, returning the exit status. An input that cannot be
+// read is reported on standard error, and the others are still checked.
+fn run<T: Int + Serialize>(options: &Options) -> i32 {
+    let mut outputs = Vec::new();
+    let mut unreadable = false;
+    for path in options.paths.iter() {
+        let ranges = match read_ranges::<T>(path) {
+            Ok(ranges) => ranges,
+            Err(message) => {
+                eprintln!("{}: {}", path, message);
+                unreadable = true;
+                continue;
+            },
+        };
+        let report = CheckReport::new(&ranges);
+        outputs.push(Output{
+            input: path,
+            ty: T::NAME,
+            exhaustive: report.is_exhaustive(),
+            disjoint: report.is_disjoint(),
+            report,
+        });
+    }
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&outputs).unwrap());
+    } else {
+        for output in outputs.iter() {
+            print_text(output);
+        }
+    }
+    let failed = outputs.iter().any(|output| {
+        (options.require_exhaustive && !output.exhaustive) ||
+            (options.require_disjoint && !output.disjoint)
+    });
+    if unreadable {
+        2
+    } else if failed {
+        1
+    } else {
+        0
+    }
+}
+
+// Read and validate the list of ranges in a file, or in standard input.
+fn read_ranges<T: Int>(path: &str) -> Result<Vec<IntRange<T>>, String> {
+    let mut text = String::new();
+    let result = if path == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        fs::read_to_string(path).map(|contents| text = contents)
+    };
+    result.map_err(|error| error.to_string())?;
+    // A list may continue on the next line after a separator, so lines are
+    // joined before parsing. A line that ends without one gets a comma.
+    let mut joined = String::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if !joined.is_empty() &&
+            !joined.trim_end().ends_with([',', '|', '∪', '[']) &&
+            !line.starts_with([',', '|', '∪', ']']) {
+            joined.push(',');
+        }
+        joined.push_str(line);
+        joined.push(' ');
+    }
+    let joined = trim_last_separator(&joined);
+    let ranges = parse_ranges::<T>(&joined).map_err(|error| {
+        match near(&joined, error.position) {
+            "" => format!("{} (at end of input)", error),
+            text => format!("{} (near `{}`)", error, text),
+        }
+    })?;
+    validate_ranges(ranges, Validation::Strict)
+        .map_err(|error| error.to_string())
+}
+
+// Drop a separator left after the last range, at the end of the list or
+// before its closing bracket.
+fn trim_last_separator(list: &str) -> String {
+    let list = list.trim_end();
+    let (ranges, close) = match list.strip_suffix(']') {
+        Some(ranges) => (ranges.trim_end(), "]"),
+        None => (list, ""),
+    };
+    match ranges.strip_suffix([',', '|', '∪']) {
+        Some(ranges) => format!("{}{}", ranges, close),
+        None => list.to_string(),
+    }
+}
+
+// Quote a little of the input around a parse error.
+fn near(input: &str, position: usize) -> &str {
+    let rest = input[position..].trim_start();
+    let end = rest.char_indices().nth(16).map_or(rest.len(), |(i, _)| i);
+    rest[..end].trim_end()
+}
+
+fn print_text<T: Int + Serialize>(output: &Output<T>) {
+    let style = RangeStyle::new(Notation::Pattern);
+    let report = &output.report;
+    if output.exhaustive && output.disjoint {
+        println!("{}: exhaustive and disjoint", output.input);
+    }
+    if !output.exhaustive {
+        println!("{}: uncovered: {}", output.input,
+                 RangeList::new(&report.uncovered).style(style));
+    }
+    for overlap in report.provenance.iter() {
+        let sources: Vec<String> =
+            overlap.sources.iter().map(|i| i.to_string()).collect();
+        println!("{}: ranges {} overlap on {}", output.input,
+                 sources.join(", "), overlap.range.styled(style));
+    }
+}
@@ -0,0 +1,44 @@
+//! Lints the integer `match` expressions in Rust source files.
+//!
+//! Usage: `int_range_lint FILE...`
+//!
+//! Each finding is printed as `FILE:LINE:COLUMN: message`. The exit status
+//! is 0 if there are no findings, 1 if there are some, and 2 if a file could
+//! not be read or parsed.
+
+use std::{env, fs, process};
+
+use int_range_check::lint::lint_source;
+
+fn main() {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("usage: int_range_lint FILE...");
+        process::exit(2);
+    }
+    let mut status = 0;
+    for path in paths.iter() {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                status = 2;
+                continue;
+            },
+        };
+        match lint_source(&source) {
+            Ok(findings) => for finding in findings {
+                println!("{}:{}:{}: {}", path, finding.line, finding.column,
+                         finding);
+                status = status.max(1);
+            },
+            Err(error) => {
+                let start = error.span().start();
+                eprintln!("{}:{}:{}: {}", path, start.line, start.column + 1,
+                          error);
+                status = 2;
+            },
+        }
+    }
+    process::exit(status);
+}
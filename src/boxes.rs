@@ -0,0 +1,251 @@
+//! Multi-dimensional ranges: boxes of values, as matched by tuple patterns
+//! such as `(0..=9, 10..)`.
+
+use std::borrow::Borrow;
+use std::fmt::{self, Display, Formatter};
+
+use super::{Int, IntRange, MergeRange};
+use super::MergeResult::*;
+
+/// A box in `N` dimensions: one range per axis, like the pattern of a match
+/// arm over an `N`-tuple.
+///
+/// Every axis has the same integer type. A box is empty if any of its ranges
+/// is empty.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RangeBox<T: Int, const N: usize>(pub [IntRange<T>; N]);
+
+impl<T: Int, const N: usize> RangeBox<T, N> {
+    /// Returns the box containing every point.
+    pub fn full() -> Self {
+        RangeBox([IntRange::Full; N])
+    }
+    /// Returns true if the box contains no points.
+    pub fn is_empty(&self) -> bool {
+        self.axis_ranges().is_none()
+    }
+    /// Returns true if the box contains `point`.
+    pub fn contains(&self, point: [T; N]) -> bool {
+        match self.axis_ranges() {
+            Some(ranges) => ranges.iter().zip(point.iter())
+                .all(|(range, &x)| range.start <= x && x <= range.end),
+            None => false,
+        }
+    }
+    fn axis_ranges(&self) -> Option<Vec<MergeRange<T>>> {
+        self.0.iter().map(|range| range.to_merge_range()).collect()
+    }
+    fn from_merge_ranges(ranges: &[MergeRange<T>]) -> Self {
+        RangeBox(std::array::from_fn(
+            |axis| IntRange::from_merge_range(ranges[axis])))
+    }
+}
+
+impl<T: Int, const N: usize> From<[IntRange<T>; N]> for RangeBox<T, N> {
+    fn from(ranges: [IntRange<T>; N]) -> Self {
+        RangeBox(ranges)
+    }
+}
+
+impl<T: Int, const N: usize> Display for RangeBox<T, N> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        formatter.write_str("(")?;
+        for (axis, range) in self.0.iter().enumerate() {
+            if axis > 0 {
+                formatter.write_str(", ")?;
+            }
+            write!(formatter, "{}", range)?;
+        }
+        formatter.write_str(")")
+    }
+}
+
+/// The multi-dimensional version of `uncovered_and_overlapped`. Returns:
+///
+///  1) a list of boxes covering the points which are not covered by any
+///     input box, and
+///
+///  2) a list of boxes covering the points which are covered by more than
+///     one input box.
+///
+/// Each list consists of disjoint boxes in lexicographic order. The space is
+/// cut into slabs along the first axis, each slab is handled recursively
+/// along the remaining axes, and neighboring slabs with the same cross
+/// section are merged, so the lists are as short as that allows. Empty input
+/// boxes are ignored.
+#[allow(clippy::type_complexity)]
+pub fn uncovered_and_overlapped_boxes<T, const N: usize, I>(boxes: I)
+      -> (Vec<RangeBox<T, N>>, Vec<RangeBox<T, N>>)
+    where T: Int, I: IntoIterator, I::Item: Borrow<RangeBox<T, N>> {
+    let boxes: Vec<Vec<MergeRange<T>>> = boxes.into_iter()
+        .filter_map(|range_box| range_box.borrow().axis_ranges())
+        .collect();
+    let slices: Vec<&[MergeRange<T>]> =
+        boxes.iter().map(|ranges| &ranges[..]).collect();
+    let uncovered = region(&slices, N, &|depth| depth == 0);
+    let overlapped = region(&slices, N, &|depth| depth > 1);
+    (uncovered.iter().map(|ranges| RangeBox::from_merge_ranges(ranges))
+         .collect(),
+     overlapped.iter().map(|ranges| RangeBox::from_merge_ranges(ranges))
+         .collect())
+}
+
+// A list of disjoint boxes, each given by its ranges on some of the axes.
+type Region<T> = Vec<Vec<MergeRange<T>>>;
+
+// Find the part of the space spanned by the last `axes` axes where the
+// number of covering boxes satisfies `keep`, as a list of disjoint boxes.
+// Each input box holds just its ranges on those axes.
+fn region<T, F>(boxes: &[&[MergeRange<T>]], axes: usize, keep: &F)
+      -> Region<T>
+    where T: Int, F: Fn(usize) -> bool {
+    if axes == 0 {
+        return if keep(boxes.len()) { vec![Vec::new()] } else { Vec::new() };
+    }
+    // Cut the first axis into slabs, such that every box either spans a
+    // slab or misses it.
+    let mut cuts = vec![T::min_value()];
+    for ranges in boxes.iter() {
+        cuts.push(ranges[0].start);
+        if let Some(past_end) = ranges[0].end.successor() {
+            cuts.push(past_end);
+        }
+    }
+    cuts.sort();
+    cuts.dedup();
+
+    let mut boxes_out = Vec::new();
+    let mut last: Option<(MergeRange<T>, Region<T>)> = None;
+    for (i, &start) in cuts.iter().enumerate() {
+        let slab = match cuts.get(i + 1) {
+            Some(next) => MergeRange::from_range(start,
+                                                 next.predecessor().unwrap()),
+            None => MergeRange::from_range_from(start),
+        };
+        let spanning: Vec<&[MergeRange<T>]> = boxes.iter()
+            .filter(|ranges| ranges[0].start <= slab.start &&
+                    slab.end <= ranges[0].end)
+            .map(|ranges| &ranges[1..])
+            .collect();
+        let section = region(&spanning, axes - 1, keep);
+        // Extend the previous slab if it has the same cross section.
+        if let Some((ref mut last_slab, ref last_section)) = last {
+            if *last_section == section {
+                if let Adjacent(concat) = last_slab.merge(slab) {
+                    *last_slab = concat;
+                    continue;
+                }
+            }
+        }
+        if let Some((last_slab, last_section)) = last.take() {
+            push_slab(&mut boxes_out, last_slab, last_section);
+        }
+        last = Some((slab, section));
+    }
+    if let Some((last_slab, last_section)) = last {
+        push_slab(&mut boxes_out, last_slab, last_section);
+    }
+    boxes_out
+}
+
+// Add the boxes of a slab to a region, prepending the slab's range on the
+// first axis to each box of its cross section.
+fn push_slab<T: Int>(region: &mut Region<T>, slab: MergeRange<T>,
+                     section: Region<T>) {
+    for ranges in section {
+        let mut full_ranges = Vec::with_capacity(ranges.len() + 1);
+        full_ranges.push(slab);
+        full_ranges.extend(ranges);
+        region.push(full_ranges);
+    }
+}
+
+#[cfg(test)]
+mod boxes_tests {
+    use super::{RangeBox, uncovered_and_overlapped_boxes};
+    use super::super::IntRange;
+    use super::super::IntRange::*;
+    #[test]
+    fn no_boxes_is_uncovered() {
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_boxes(Vec::<RangeBox<u8, 2>>::new());
+        assert_eq!(uncovered, vec![RangeBox::full()]);
+        assert_eq!(overlapped, vec![]);
+    }
+    #[test]
+    fn cross_of_two_strips() {
+        let (uncovered, overlapped) = uncovered_and_overlapped_boxes([
+            RangeBox([To(5u8), Full]),
+            RangeBox([Full, To(5u8)]),
+            ]);
+        assert_eq!(uncovered, vec![RangeBox([From(6), From(6)])]);
+        assert_eq!(overlapped, vec![RangeBox([To(5), To(5)])]);
+    }
+    #[test]
+    fn exhaustive_and_disjoint_tiling() {
+        let boxes = [
+            RangeBox([To(-1i32), Full]),
+            RangeBox([From(0i32), To(9)]),
+            RangeBox([From(0i32), From(10)]),
+            ];
+        assert_eq!(uncovered_and_overlapped_boxes(boxes),
+                   (vec![], vec![]));
+    }
+    #[test]
+    fn slabs_with_same_section_merge() {
+        // Two boxes side by side leave one uncovered strip above them.
+        let (uncovered, _) = uncovered_and_overlapped_boxes([
+            RangeBox([Full, To(9u8)]),
+            RangeBox([To(100u8), Bound(10, 20)]),
+            RangeBox([From(101u8), Bound(10, 20)]),
+            ]);
+        assert_eq!(uncovered, vec![RangeBox([Full, From(21)])]);
+    }
+    #[test]
+    fn three_dimensions() {
+        let (uncovered, overlapped) = uncovered_and_overlapped_boxes([
+            RangeBox([To(0u8), Full, Full]),
+            RangeBox([From(1u8), Full, Full]),
+            RangeBox([Bound(0u8, 1), Bound(2, 2), Bound(3, 4)]),
+            ]);
+        assert_eq!(uncovered, vec![]);
+        assert_eq!(overlapped, vec![
+            RangeBox([To(1), Bound(2, 2), Bound(3, 4)])]);
+    }
+    #[test]
+    fn empty_boxes_ignored() {
+        let empty = RangeBox([Bound(5u8, 1), Full]);
+        assert!(empty.is_empty());
+        assert!(!empty.contains([3, 3]));
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_boxes([empty, RangeBox::full()]);
+        assert_eq!((uncovered, overlapped), (vec![], vec![]));
+    }
+    #[test]
+    fn matches_point_by_point_count() {
+        let boxes = [
+            RangeBox([Bound(0i8, 4), Bound(-3, 3)]),
+            RangeBox([Bound(2i8, 9), Bound(0, 5)]),
+            RangeBox([To(-100i8), From(100)]),
+            RangeBox([Bound(3i8, 3), Full]),
+            ];
+        let (uncovered, overlapped) = uncovered_and_overlapped_boxes(boxes);
+        for x in i8::MIN..=i8::MAX {
+            for y in i8::MIN..=i8::MAX {
+                let depth = boxes.iter().filter(|b| b.contains([x, y])).count();
+                let in_uncovered = uncovered.iter()
+                    .filter(|b| b.contains([x, y])).count();
+                let in_overlapped = overlapped.iter()
+                    .filter(|b| b.contains([x, y])).count();
+                assert_eq!(in_uncovered, (depth == 0) as usize);
+                assert_eq!(in_overlapped, (depth > 1) as usize);
+            }
+        }
+    }
+    #[test]
+    fn display_box() {
+        assert_eq!(format!("{}", RangeBox([IntRange::To(4u8),
+                                           IntRange::Bound(7, 9)])),
+                   "(4 and below, 7-9)");
+    }
+}
@@ -0,0 +1,319 @@
+//! Range checks that can run in constant evaluation, such as
+//! `const _: () = assert!(is_exhaustive(&TABLE));`.
+//!
+//! Trait methods cannot be called from a `const fn`, so these functions are
+//! not generic over `Int`. Instead, there is one module per type, named after
+//! it: `const_eval::u8::is_exhaustive` checks ranges of `u8`, and so on.
+//!
+//! The input tables are not sorted, so the checks take quadratic time or
+//! worse in the number of ranges. This is fine for the tables written by hand
+//! that constant assertions are meant for. Empty ranges are ignored, as in
+//! `uncovered_and_overlapped`.
+
+use super::{Int, IntRange};
+
+/// A list of at most `N` ranges, as returned by `complement`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RangeArray<T: Int, const N: usize> {
+    ranges: [IntRange<T>; N],
+    len: usize,
+}
+
+impl<T: Int, const N: usize> RangeArray<T, N> {
+    /// Returns the ranges in the list.
+    pub const fn as_slice(&self) -> &[IntRange<T>] {
+        self.ranges.split_at(self.len).0
+    }
+    /// Returns the number of ranges in the list.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns true if the list has no ranges.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// Define the functions of a type's module, given `successor` and
+// `predecessor` functions for the type.
+macro_rules! const_fns {
+    ($t:ident) => {
+        use super::super::{IntRange, MergeRange};
+        use super::super::MergeResult::{self, *};
+        use super::RangeArray;
+
+        /// Returns the union of two ranges if it is a single, nonempty
+        /// range, as when they overlap or are adjacent.
+        pub const fn merge(x: IntRange<$t>, y: IntRange<$t>)
+              -> Option<IntRange<$t>> {
+            match (merge_range(x), merge_range(y)) {
+                (Some(x), Some(y)) => match merge_ranges(x, y) {
+                    Adjacent(union) | Overlap(union, _) =>
+                        Some(from_merge_range(union)),
+                    Separate => None,
+                },
+                (Some(x), None) | (None, Some(x)) =>
+                    Some(from_merge_range(x)),
+                (None, None) => None,
+            }
+        }
+
+        /// Returns the intersection of two ranges, or `None` if it is
+        /// empty.
+        pub const fn intersection(x: IntRange<$t>, y: IntRange<$t>)
+              -> Option<IntRange<$t>> {
+            match (merge_range(x), merge_range(y)) {
+                (Some(x), Some(y)) => match merge_ranges(x, y) {
+                    Overlap(_, overlap) => Some(from_merge_range(overlap)),
+                    Adjacent(_) | Separate => None,
+                },
+                _ => None,
+            }
+        }
+
+        /// Returns the ranges not covered by any input range, in ascending
+        /// order.
+        ///
+        /// The capacity `N` of the result is chosen by the caller; the
+        /// complement of `n` ranges has at most `n + 1` ranges. Panics, and
+        /// so fails constant evaluation, if the complement does not fit.
+        pub const fn complement<const N: usize>(ranges: &[IntRange<$t>])
+              -> RangeArray<$t, N> {
+            let mut gaps = RangeArray{ranges: [IntRange::Full; N], len: 0};
+            let mut next = Some(<$t>::MIN);
+            while let Some(from) = next {
+                let gap = match next_gap(ranges, from) {
+                    Some(gap) => gap,
+                    None => break,
+                };
+                if gaps.len == N {
+                    panic!("complement does not fit in the array");
+                }
+                gaps.ranges[gaps.len] = from_merge_range(gap);
+                gaps.len += 1;
+                next = successor(gap.end);
+            }
+            gaps
+        }
+
+        /// Returns true if every value of the type is covered by some input
+        /// range.
+        pub const fn is_exhaustive(ranges: &[IntRange<$t>]) -> bool {
+            next_gap(ranges, <$t>::MIN).is_none()
+        }
+
+        /// Returns true if no value is covered by more than one input range.
+        pub const fn is_disjoint(ranges: &[IntRange<$t>]) -> bool {
+            let mut i = 0;
+            while i < ranges.len() {
+                let mut j = i + 1;
+                while j < ranges.len() {
+                    if let (Some(x), Some(y)) = (merge_range(ranges[i]),
+                                                 merge_range(ranges[j])) {
+                        if let Overlap(..) = merge_ranges(x, y) {
+                            return false;
+                        }
+                    }
+                    j += 1;
+                }
+                i += 1;
+            }
+            true
+        }
+
+        // Find the first gap in the input ranges at or above `from`.
+        const fn next_gap(ranges: &[IntRange<$t>], from: $t)
+              -> Option<MergeRange<$t>> {
+            // Skip past the ranges covering `from`, one at a time.
+            let mut start = from;
+            let mut i = 0;
+            while i < ranges.len() {
+                if let Some(range) = merge_range(ranges[i]) {
+                    if range.start <= start && start <= range.end {
+                        start = match successor(range.end) {
+                            Some(next) => next,
+                            None => return None,
+                        };
+                        // Earlier ranges may cover the new start.
+                        i = 0;
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+            // The gap ends just before the next range above it.
+            let mut end = <$t>::MAX;
+            let mut i = 0;
+            while i < ranges.len() {
+                if let Some(range) = merge_range(ranges[i]) {
+                    if start < range.start && range.start <= end {
+                        end = match predecessor(range.start) {
+                            Some(end) => end,
+                            None => unreachable!(),
+                        };
+                    }
+                }
+                i += 1;
+            }
+            Some(MergeRange{start, end})
+        }
+
+        // The same as `IntRange::to_merge_range`.
+        const fn merge_range(range: IntRange<$t>) -> Option<MergeRange<$t>> {
+            let (start, end) = match range {
+                IntRange::Bound(start, end) => (start, end),
+                IntRange::To(end) => (<$t>::MIN, end),
+                IntRange::From(start) => (start, <$t>::MAX),
+                IntRange::Full => (<$t>::MIN, <$t>::MAX),
+            };
+            if start <= end {
+                Some(MergeRange{start, end})
+            } else {
+                None
+            }
+        }
+
+        // The same as `IntRange::from_merge_range`.
+        const fn from_merge_range(range: MergeRange<$t>) -> IntRange<$t> {
+            match (range.start > <$t>::MIN, range.end < <$t>::MAX) {
+                (true, true) => IntRange::Bound(range.start, range.end),
+                (true, false) => IntRange::From(range.start),
+                (false, true) => IntRange::To(range.end),
+                (false, false) => IntRange::Full,
+            }
+        }
+
+        // The same as `MergeRange::merge`.
+        const fn merge_ranges(x: MergeRange<$t>, y: MergeRange<$t>)
+              -> MergeResult<$t> {
+            if is_next(x.end, y.start) {
+                return Adjacent(MergeRange{start: x.start, end: y.end});
+            }
+            if is_next(y.end, x.start) {
+                return Adjacent(MergeRange{start: y.start, end: x.end});
+            }
+            if x.start <= y.end && y.start <= x.end {
+                let (union_start, overlap_start) = if x.start < y.start {
+                    (x.start, y.start)
+                } else {
+                    (y.start, x.start)
+                };
+                let (overlap_end, union_end) = if x.end < y.end {
+                    (x.end, y.end)
+                } else {
+                    (y.end, x.end)
+                };
+                Overlap(MergeRange{start: union_start, end: union_end},
+                        MergeRange{start: overlap_start, end: overlap_end})
+            } else {
+                Separate
+            }
+        }
+
+        // Returns true if `y` is the successor of `x`.
+        const fn is_next(x: $t, y: $t) -> bool {
+            match successor(x) {
+                Some(next) => next == y,
+                None => false,
+            }
+        }
+    }
+}
+
+macro_rules! int_modules {
+    ($($t:ident),*) => {$(
+        #[doc = concat!("Constant range checks for `", stringify!($t), "`.")]
+        pub mod $t {
+            const fn successor(x: $t) -> Option<$t> {
+                x.checked_add(1)
+            }
+            const fn predecessor(x: $t) -> Option<$t> {
+                x.checked_sub(1)
+            }
+            const_fns!($t);
+        }
+    )*}
+}
+
+int_modules!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Constant range checks for `char`.
+pub mod char {
+    // As in the `Int` implementation, skip the surrogate code points.
+    const fn successor(x: char) -> Option<char> {
+        match x {
+            '\u{D7FF}' => Some('\u{E000}'),
+            char::MAX => None,
+            _ => char::from_u32(x as u32 + 1),
+        }
+    }
+    const fn predecessor(x: char) -> Option<char> {
+        match x {
+            '\u{E000}' => Some('\u{D7FF}'),
+            char::MIN => None,
+            _ => char::from_u32(x as u32 - 1),
+        }
+    }
+    const_fns!(char);
+}
+
+#[cfg(test)]
+mod const_eval_tests {
+    use super::RangeArray;
+    use super::super::{IntRange, const_eval, uncovered_and_overlapped};
+    use super::super::IntRange::*;
+    const TABLE: [IntRange<u8>; 4] =
+        [Bound(10, 99), To(9), Bound(200, 255), Bound(100, 199)];
+    const _: () = assert!(const_eval::u8::is_exhaustive(&TABLE));
+    const _: () = assert!(const_eval::u8::is_disjoint(&TABLE));
+    const GAPS: RangeArray<u8, 2> =
+        const_eval::u8::complement(&[Bound(10, 20)]);
+    #[test]
+    fn constant_table_checked() {
+        assert!(const_eval::u8::complement::<0>(&TABLE).is_empty());
+        assert_eq!(GAPS.as_slice(), &[To(9), From(21)]);
+        assert_eq!(const_eval::u8::complement::<1>(&[]).as_slice(),
+                   &[Full]);
+    }
+    #[test]
+    fn merge_and_intersection() {
+        use super::super::const_eval::u8::{intersection, merge};
+        assert_eq!(merge(To(9), Bound(10, 20)), Some(To(20)));
+        assert_eq!(merge(To(9), Bound(11, 20)), None);
+        assert_eq!(merge(Bound(5, 1), From(7)), Some(From(7)));
+        assert_eq!(intersection(To(15), Bound(10, 20)), Some(Bound(10, 15)));
+        assert_eq!(intersection(To(9), From(10)), None);
+        assert_eq!(const_eval::i8::merge(From(0), To(-1)), Some(Full));
+    }
+    #[test]
+    fn matches_uncovered_and_overlapped() {
+        use super::super::const_eval::i8::{complement, is_disjoint,
+                                           is_exhaustive};
+        let tables: [&[IntRange<i8>]; 5] = [
+            &[],
+            &[Full],
+            &[Bound(-5, 5), Bound(10, 20), Bound(15, 30), Bound(3, 1)],
+            &[To(-100), Bound(0, 0), From(100), Bound(0, 0)],
+            &[From(1), To(-1), Bound(-1, 1)],
+            ];
+        for &table in tables.iter() {
+            let (uncovered, overlapped) = uncovered_and_overlapped(table);
+            assert_eq!(complement::<5>(table).as_slice(), &uncovered[..]);
+            assert_eq!(is_exhaustive(table), uncovered.is_empty());
+            assert_eq!(is_disjoint(table), overlapped.is_empty());
+        }
+    }
+    #[test]
+    #[should_panic]
+    fn complement_overflow_panics() {
+        const_eval::u8::complement::<1>(&[Bound(10, 20)]);
+    }
+    #[test]
+    fn char_skips_surrogates() {
+        let table = [To('\u{D7FF}'), From('\u{E000}')];
+        assert!(const_eval::char::is_exhaustive(&table));
+        assert_eq!(const_eval::char::merge(table[0], table[1]), Some(Full));
+        assert_eq!(const_eval::char::complement::<2>(&[To('a')]).as_slice(),
+                   &[From('b')]);
+    }
+}
@@ -0,0 +1,201 @@
+//! Conversions between `IntRange` and the standard library range types.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{self, RangeBounds};
+
+use super::{Int, IntRange, MergeRange};
+
+/// Error returned when converting an empty half-open range to an `IntRange`.
+///
+/// A half-open range such as `5..5` contains no values. An empty
+/// `IntRange::Bound` needs an end below its start, which does not exist for
+/// ranges ending at `T::MIN`, so all empty half-open ranges are rejected.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct EmptyRangeError;
+
+impl Display for EmptyRangeError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        formatter.write_str("half-open range is empty")
+    }
+}
+
+impl Error for EmptyRangeError {}
+
+impl<T: Int> IntRange<T> {
+    /// Converts any range type to an `IntRange`, returning `None` if it is
+    /// empty.
+    pub fn from_range_bounds<R: RangeBounds<T>>(range: &R) -> Option<Self> {
+        merge_range_from_bounds(range).map(IntRange::from_merge_range)
+    }
+}
+
+// Convert any range type to the internal representation, returning `None` if
+// it is empty.
+pub(crate) fn merge_range_from_bounds<T, R>(range: &R)
+        -> Option<MergeRange<T>>
+    where T: Int, R: RangeBounds<T> + ?Sized {
+    let start = match range.start_bound() {
+        ops::Bound::Included(&start) => start,
+        ops::Bound::Excluded(&start) => start.successor()?,
+        ops::Bound::Unbounded => T::min_value(),
+    };
+    let end = match range.end_bound() {
+        ops::Bound::Included(&end) => end,
+        ops::Bound::Excluded(&end) => end.predecessor()?,
+        ops::Bound::Unbounded => T::max_value(),
+    };
+    if start <= end {
+        Some(MergeRange::from_range(start, end))
+    } else {
+        None
+    }
+}
+
+// Convert input ranges to the internal representation, dropping empty ones.
+pub(crate) fn merge_ranges<T, I>(ranges: I) -> Vec<MergeRange<T>>
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
+    ranges.into_iter()
+        .filter_map(|range| merge_range_from_bounds(&range))
+        .collect()
+}
+
+impl<T: Int> RangeBounds<T> for IntRange<T> {
+    fn start_bound(&self) -> ops::Bound<&T> {
+        match *self {
+            IntRange::Bound(ref start, _) | IntRange::From(ref start) =>
+                ops::Bound::Included(start),
+            IntRange::To(_) | IntRange::Full => ops::Bound::Unbounded,
+        }
+    }
+    fn end_bound(&self) -> ops::Bound<&T> {
+        match *self {
+            IntRange::Bound(_, ref end) | IntRange::To(ref end) =>
+                ops::Bound::Included(end),
+            IntRange::From(_) | IntRange::Full => ops::Bound::Unbounded,
+        }
+    }
+}
+
+// This allows tables of `IntRange`s to be checked without copying them.
+impl<T: Int> RangeBounds<T> for &IntRange<T> {
+    fn start_bound(&self) -> ops::Bound<&T> {
+        (**self).start_bound()
+    }
+    fn end_bound(&self) -> ops::Bound<&T> {
+        (**self).end_bound()
+    }
+}
+
+impl<T: Int> From<ops::RangeInclusive<T>> for IntRange<T> {
+    fn from(range: ops::RangeInclusive<T>) -> Self {
+        let (start, end) = range.into_inner();
+        IntRange::Bound(start, end)
+    }
+}
+
+impl<T: Int> From<ops::RangeToInclusive<T>> for IntRange<T> {
+    fn from(range: ops::RangeToInclusive<T>) -> Self {
+        IntRange::To(range.end)
+    }
+}
+
+impl<T: Int> From<ops::RangeFrom<T>> for IntRange<T> {
+    fn from(range: ops::RangeFrom<T>) -> Self {
+        IntRange::From(range.start)
+    }
+}
+
+impl<T: Int> From<ops::RangeFull> for IntRange<T> {
+    fn from(_: ops::RangeFull) -> Self {
+        IntRange::Full
+    }
+}
+
+impl<T: Int> TryFrom<ops::Range<T>> for IntRange<T> {
+    type Error = EmptyRangeError;
+    fn try_from(range: ops::Range<T>) -> Result<Self, EmptyRangeError> {
+        if range.start < range.end {
+            Ok(IntRange::Bound(range.start, range.end.predecessor().unwrap()))
+        } else {
+            Err(EmptyRangeError)
+        }
+    }
+}
+
+impl<T: Int> TryFrom<ops::RangeTo<T>> for IntRange<T> {
+    type Error = EmptyRangeError;
+    fn try_from(range: ops::RangeTo<T>) -> Result<Self, EmptyRangeError> {
+        range.end.predecessor().map(IntRange::To).ok_or(EmptyRangeError)
+    }
+}
+
+impl<T: Int> From<IntRange<T>> for ops::RangeInclusive<T> {
+    fn from(range: IntRange<T>) -> Self {
+        match range {
+            IntRange::Bound(start, end) => start..=end,
+            IntRange::To(end) => T::min_value()..=end,
+            IntRange::From(start) => start..=T::max_value(),
+            IntRange::Full => T::min_value()..=T::max_value(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod convert_tests {
+    use std::convert::TryFrom;
+    use std::ops::RangeInclusive;
+    use super::EmptyRangeError;
+    use super::super::{IntRange, uncovered_and_overlapped};
+    #[test]
+    fn inclusive_ranges_convert() {
+        assert_eq!(IntRange::from(3u8..=7), IntRange::Bound(3u8, 7));
+        assert_eq!(IntRange::from(..=7u8), IntRange::To(7u8));
+        assert_eq!(IntRange::from(3u8..), IntRange::From(3u8));
+        assert_eq!(IntRange::<u8>::from(..), IntRange::Full);
+    }
+    #[test]
+    fn half_open_ranges_convert() {
+        assert_eq!(IntRange::try_from(3i16..8), Ok(IntRange::Bound(3i16, 7)));
+        assert_eq!(IntRange::try_from(..8i16), Ok(IntRange::To(7i16)));
+    }
+    #[test]
+    fn empty_half_open_ranges_fail() {
+        assert_eq!(IntRange::try_from(5i16..5), Err(EmptyRangeError));
+        let (start, end) = (5i16, 2i16);
+        assert_eq!(IntRange::try_from(start..end), Err(EmptyRangeError));
+        assert_eq!(IntRange::try_from(i16::MIN..i16::MIN),
+                   Err(EmptyRangeError));
+        assert_eq!(IntRange::try_from(..i16::MIN), Err(EmptyRangeError));
+    }
+    #[test]
+    fn convert_to_range_inclusive() {
+        assert_eq!(RangeInclusive::from(IntRange::Bound(3u8, 7)), 3..=7);
+        assert_eq!(RangeInclusive::from(IntRange::To(7u8)), 0..=7);
+        assert_eq!(RangeInclusive::from(IntRange::From(3u8)), 3..=255);
+        assert_eq!(RangeInclusive::from(IntRange::<u8>::Full), 0..=255);
+    }
+    #[test]
+    fn from_range_bounds_normalizes() {
+        assert_eq!(IntRange::from_range_bounds(&(0u8..10)),
+                   Some(IntRange::To(9u8)));
+        assert_eq!(IntRange::from_range_bounds(&(1u8..=u8::MAX)),
+                   Some(IntRange::From(1u8)));
+        assert_eq!(IntRange::from_range_bounds(&(5u8..5)), None);
+        assert_eq!(IntRange::from_range_bounds(&IntRange::Bound(5u8, 1)),
+                   None);
+    }
+    #[test]
+    fn check_std_ranges() {
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped([0i32..10, 5..20, 30..30]);
+        assert_eq!(uncovered,
+                   vec![IntRange::To(-1i32), IntRange::From(20)]);
+        assert_eq!(overlapped, vec![IntRange::Bound(5i32, 9)]);
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped(vec![..=10u8, ..=u8::MAX]);
+        assert_eq!(uncovered, vec![]);
+        assert_eq!(overlapped, vec![IntRange::To(10u8)]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Coverage depth: how many input ranges cover each part of the type.
+
+use std::collections::BTreeSet;
+use std::ops::RangeBounds;
+
+use super::{Int, IntRange, MergeRange, RangeSet};
+use super::convert::merge_range_from_bounds;
+use super::MergeResult::*;
+
+/// The values of an integer type divided into disjoint segments, each labeled
+/// with the number of input ranges that cover it.
+///
+/// The segments cover the whole type in ascending order, and adjacent
+/// segments always have different depths. A depth of zero means that the
+/// segment is uncovered.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CoverageMap<T: Int> {
+    segments: Vec<(MergeRange<T>, usize)>,
+}
+
+impl<T: Int> CoverageMap<T> {
+    /// Computes the coverage depth of a list of ranges. The input is
+    /// accepted in the same forms as for `uncovered_and_overlapped`, and
+    /// empty ranges are ignored.
+    pub fn new<I>(ranges: I) -> Self
+        where I: IntoIterator, I::Item: RangeBounds<T> {
+        let merge_ranges =
+            ranges.into_iter().map(|range| merge_range_from_bounds(&range));
+        let mut coverage_map = CoverageMap{segments: Vec::new()};
+        sweep(merge_ranges, |range, active| {
+            coverage_map.push_segment(range, active.len());
+        });
+        coverage_map
+    }
+    /// Iterates over the segments in ascending order, with their depths.
+    pub fn segments(&self) -> Segments<'_, T> {
+        Segments{inner: self.segments.iter()}
+    }
+    /// Returns the number of input ranges containing `value`.
+    pub fn depth_at(&self, value: T) -> usize {
+        // The segments cover the whole type, so the last segment starting at
+        // or below the value must contain it.
+        let i = self.segments.partition_point(|x| x.0.start <= value);
+        self.segments[i - 1].1
+    }
+    /// Returns the largest depth of any segment.
+    pub fn max_depth(&self) -> usize {
+        self.segments.iter().map(|x| x.1).max().unwrap_or(0)
+    }
+    /// Returns the set of values covered by at least `k` input ranges.
+    ///
+    /// With `k` equal to 1, this is the set of covered values. With `k` equal
+    /// to 2, it is the set of overlapped values.
+    pub fn at_least(&self, k: usize) -> RangeSet<T> {
+        let mut range_set = RangeSet::new();
+        for &(range, depth) in self.segments.iter() {
+            if depth >= k {
+                range_set.push_last(range);
+            }
+        }
+        range_set
+    }
+    // Append a segment, merging it into the last one if the depth is the
+    // same.
+    fn push_segment(&mut self, range: MergeRange<T>, depth: usize) {
+        if let Some(last) = self.segments.last_mut() {
+            if last.1 == depth {
+                if let Adjacent(concat) = last.0.merge(range) {
+                    last.0 = concat;
+                    return;
+                }
+            }
+        }
+        self.segments.push((range, depth));
+    }
+}
+
+// Sweep over the range boundaries, calling `visit` on each segment of the
+// type in ascending order, with the set of inputs that are active in it.
+// Empty input ranges are `None`, so that indices are preserved.
+pub(crate) fn sweep<T, I, F>(ranges: I, mut visit: F)
+    where T: Int, I: Iterator<Item=Option<MergeRange<T>>>,
+          F: FnMut(MergeRange<T>, &BTreeSet<usize>) {
+    // Events are (point, index, starting). A range starts at its start point
+    // and stops just past its end point (unless it ends at the maximum of the
+    // type).
+    let mut events = Vec::new();
+    for (i, range) in ranges.enumerate() {
+        if let Some(merge_range) = range {
+            events.push((merge_range.start, i, true));
+            if let Some(past_end) = merge_range.end.successor() {
+                events.push((past_end, i, false));
+            }
+        }
+    }
+    events.sort_by_key(|event| event.0);
+
+    let mut active = BTreeSet::new();
+    let mut segment_start = T::min_value();
+    let mut i = 0;
+    while i < events.len() {
+        let point = events[i].0;
+        if point > segment_start {
+            visit(MergeRange::from_range(segment_start,
+                                         point.predecessor().unwrap()),
+                  &active);
+            segment_start = point;
+        }
+        while i < events.len() && events[i].0 == point {
+            let (_, index, starting) = events[i];
+            if starting {
+                active.insert(index);
+            } else {
+                active.remove(&index);
+            }
+            i += 1;
+        }
+    }
+    visit(MergeRange::from_range_from(segment_start), &active);
+}
+
+/// Iterator over the segments of a `CoverageMap`.
+#[derive(Clone, Debug)]
+pub struct Segments<'a, T: Int + 'a> {
+    inner: std::slice::Iter<'a, (MergeRange<T>, usize)>,
+}
+
+impl<T: Int> Iterator for Segments<'_, T> {
+    type Item = (IntRange<T>, usize);
+    fn next(&mut self) -> Option<(IntRange<T>, usize)> {
+        self.inner.next()
+            .map(|&(range, depth)| (IntRange::from_merge_range(range), depth))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Int> ExactSizeIterator for Segments<'_, T> {}
+
+#[cfg(test)]
+mod coverage_tests {
+    use super::CoverageMap;
+    use super::super::{IntRange, RangeSet, uncovered_and_overlapped};
+    #[test]
+    fn no_ranges_is_uncovered() {
+        let coverage_map = CoverageMap::new(Vec::<IntRange<u8>>::new());
+        assert_eq!(coverage_map.segments().collect::<Vec<_>>(),
+                   vec![(IntRange::Full, 0)]);
+        assert_eq!(coverage_map.max_depth(), 0);
+    }
+    #[test]
+    fn segments_count_depth() {
+        let coverage_map = CoverageMap::new([
+            IntRange::Bound(0i8, 10),
+            IntRange::Bound(5i8, 20),
+            IntRange::Bound(8i8, 9),
+            IntRange::From(21i8),
+            ]);
+        assert_eq!(coverage_map.segments().collect::<Vec<_>>(), vec![
+            (IntRange::To(-1i8), 0),
+            (IntRange::Bound(0i8, 4), 1),
+            (IntRange::Bound(5i8, 7), 2),
+            (IntRange::Bound(8i8, 9), 3),
+            (IntRange::Bound(10i8, 10), 2),
+            (IntRange::From(11i8), 1),
+            ]);
+        assert_eq!(coverage_map.max_depth(), 3);
+    }
+    #[test]
+    fn ten_way_overlap_counted() {
+        let ranges = vec![IntRange::Bound(3u16, 7); 10];
+        let coverage_map = CoverageMap::new(&ranges);
+        assert_eq!(coverage_map.depth_at(3), 10);
+        assert_eq!(coverage_map.depth_at(8), 0);
+        assert_eq!(coverage_map.at_least(10).ranges(),
+                   vec![IntRange::Bound(3u16, 7)]);
+        assert!(coverage_map.at_least(11).is_empty());
+    }
+    #[test]
+    fn depth_at_type_limits() {
+        let coverage_map = CoverageMap::new([
+            IntRange::Full,
+            IntRange::To(0u64),
+            IntRange::From(u64::MAX),
+            ]);
+        assert_eq!(coverage_map.depth_at(0), 2);
+        assert_eq!(coverage_map.depth_at(1), 1);
+        assert_eq!(coverage_map.depth_at(u64::MAX), 2);
+    }
+    #[test]
+    fn empty_bound_ignored() {
+        let coverage_map = CoverageMap::new([IntRange::Bound(5i32, 1)]);
+        assert_eq!(coverage_map.segments().collect::<Vec<_>>(),
+                   vec![(IntRange::Full, 0)]);
+    }
+    #[test]
+    fn at_least_matches_uncovered_and_overlapped() {
+        let ranges = vec![
+            IntRange::Bound(6i8, 16),
+            IntRange::To(-10i8),
+            IntRange::From(15i8),
+            IntRange::Bound(4i8, 7),
+            ];
+        let (uncovered, overlapped) = uncovered_and_overlapped(&ranges);
+        let coverage_map = CoverageMap::new(&ranges);
+        assert_eq!(coverage_map.at_least(1).complement().ranges(), uncovered);
+        assert_eq!(coverage_map.at_least(2).ranges(), overlapped);
+        assert_eq!(coverage_map.at_least(0), RangeSet::full());
+    }
+}
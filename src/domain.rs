@@ -0,0 +1,149 @@
+//! Domains given by the discriminants of fieldless enums.
+
+use std::ops::RangeBounds;
+
+use super::{DomainCheck, Int, IntRange, MergeRange, Provenance, RangeSet};
+use super::{check_in_domain, convert::merge_range_from_bounds};
+use super::provenance::overlap_segments;
+use super::MergeResult::*;
+
+/// A fieldless enum with integer discriminants, which serve as the domain for
+/// checks of the raw values that convert to it.
+///
+/// With the `derive` feature, this trait can be derived for any fieldless
+/// enum with an integer `#[repr]`, such as `#[repr(u8)]`.
+pub trait RangeDomain: Sized {
+    /// The integer type of the discriminants.
+    type Repr: Int + 'static;
+    /// The name and discriminant of each variant, in declaration order.
+    const VARIANTS: &'static [(&'static str, Self::Repr)];
+    /// Returns the discriminant of this variant.
+    fn discriminant(&self) -> Self::Repr;
+    /// Returns the set of discriminants.
+    fn domain() -> RangeSet<Self::Repr> {
+        Self::VARIANTS.iter().map(|&(_, x)| IntRange::Bound(x, x)).collect()
+    }
+    /// Checks a list of ranges against the set of discriminants, as
+    /// `check_in_domain` does.
+    fn check<I>(ranges: I) -> DomainCheck<Self::Repr>
+        where I: IntoIterator, I::Item: RangeBounds<Self::Repr> {
+        check_in_domain(ranges, &Self::domain())
+    }
+    /// Checks a table mapping ranges of raw values to variants, such as the
+    /// arms of a hand-written `TryFrom` implementation.
+    fn check_table<R, I>(table: I) -> TableCheck<Self::Repr>
+        where R: RangeBounds<Self::Repr>, I: IntoIterator<Item=(R, Self)> {
+        let mut ranges = Vec::new();
+        let mut targets = Vec::new();
+        for (range, variant) in table {
+            ranges.push(merge_range_from_bounds(&range));
+            targets.push(variant.discriminant());
+        }
+        let (covered, _) = RangeSet::from_vec_with_overlap(
+            ranges.iter().flatten().cloned().collect());
+        let forgotten = Self::VARIANTS.iter()
+            .filter(|&&(_, x)| !covered.contains(x))
+            .cloned()
+            .collect();
+        // Overlap is only ambiguous between different variants.
+        let mut ambiguous: Vec<(MergeRange<_>, Vec<_>)> = Vec::new();
+        for (range, sources) in overlap_segments(ranges.into_iter()) {
+            let mut variants: Vec<Self::Repr> = Vec::new();
+            for x in sources.into_iter().map(|i| targets[i]) {
+                if !variants.contains(&x) {
+                    variants.push(x);
+                }
+            }
+            if variants.len() < 2 {
+                continue;
+            }
+            if let Some(last) = ambiguous.last_mut() {
+                if last.1 == variants {
+                    if let Adjacent(concat) = last.0.merge(range) {
+                        last.0 = concat;
+                        continue;
+                    }
+                }
+            }
+            ambiguous.push((range, variants));
+        }
+        let name = |x| Self::VARIANTS.iter().find(|v| v.1 == x).unwrap().0;
+        TableCheck{
+            forgotten,
+            ambiguous: ambiguous.into_iter()
+                .map(|(range, variants)| Provenance{
+                    range: IntRange::from_merge_range(range),
+                    sources: variants.into_iter().map(name).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Result of checking a conversion table with `RangeDomain::check_table`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TableCheck<T: Int> {
+    /// The name and discriminant of each variant whose discriminant no range
+    /// in the table matches.
+    pub forgotten: Vec<(&'static str, T)>,
+    /// Raw values mapped to more than one variant, with the names of those
+    /// variants in table order.
+    pub ambiguous: Vec<Provenance<T, &'static str>>,
+}
+
+#[cfg(test)]
+mod domain_tests {
+    use int_range_check_macros::RangeDomain;
+    use super::{RangeDomain, TableCheck};
+    use super::super::{IntRange, Provenance, RangeSet};
+    #[derive(Clone, Copy, Debug, RangeDomain)]
+    #[repr(u8)]
+    enum Opcode {
+        Nop,
+        Load = 0x10,
+        Store,
+        Jump = 0x20,
+        r#Halt = 0xff,
+    }
+    #[derive(RangeDomain)]
+    #[repr(align(4), i16)]
+    #[allow(dead_code)]
+    enum Signed {
+        Low = -3,
+        Zero = 0,
+    }
+    #[test]
+    fn derived_variants() {
+        assert_eq!(Opcode::VARIANTS, &[("Nop", 0u8), ("Load", 0x10),
+                                       ("Store", 0x11), ("Jump", 0x20),
+                                       ("Halt", 0xff)]);
+        assert_eq!(Opcode::Store.discriminant(), 0x11);
+        assert_eq!(Signed::domain().ranges(),
+                   vec![IntRange::Bound(-3i16, -3), IntRange::Bound(0, 0)]);
+    }
+    #[test]
+    fn domain_check() {
+        let check = Opcode::check([0x00..=0x10, 0x10..=0x1f]);
+        assert_eq!(check.uncovered.ranges(),
+                   vec![IntRange::Bound(0x20u8, 0x20), IntRange::From(0xff)]);
+        assert_eq!(check.overlapped,
+                   RangeSet::from(IntRange::Bound(0x10, 0x10)));
+    }
+    #[test]
+    fn table_check() {
+        let check = Opcode::check_table([
+            (0x00..=0x00, Opcode::Nop),
+            (0x10..=0x1f, Opcode::Load),
+            (0x11..=0x11, Opcode::Store),
+            (0x10..=0x12, Opcode::Load),
+            (0x12..=0x13, Opcode::Store),
+            ]);
+        assert_eq!(check, TableCheck{
+            forgotten: vec![("Jump", 0x20), ("Halt", 0xff)],
+            ambiguous: vec![
+                Provenance{range: IntRange::Bound(0x11, 0x13),
+                           sources: vec!["Load", "Store"]},
+                ],
+        });
+    }
+}
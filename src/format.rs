@@ -0,0 +1,373 @@
+//! Configurable formatting of ranges and range lists.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{Int, IntRange};
+
+/// The notation used to write ranges.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Notation {
+    /// English prose, e.g. `[4 and below, 7-9]`.
+    Prose,
+    /// Rust pattern syntax, e.g. `i32::MIN..=-1 | 10..=20`.
+    Pattern,
+    /// Mathematical interval notation, e.g. `[-128, -1] ∪ [10, 20]`.
+    Interval,
+}
+
+/// The base used to write numbers.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Radix {
+    Decimal,
+    Binary,
+    Octal,
+    Hex,
+}
+
+impl Radix {
+    fn base(self) -> u32 {
+        match self {
+            Radix::Decimal => 10,
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Hex => 16,
+        }
+    }
+    fn prefix(self) -> &'static str {
+        match self {
+            Radix::Decimal => "",
+            Radix::Binary => "0b",
+            Radix::Octal => "0o",
+            Radix::Hex => "0x",
+        }
+    }
+}
+
+/// Options controlling how ranges are written.
+///
+/// The default is English prose with decimal numbers, which is what the
+/// `Display` implementation of `IntRange` uses.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RangeStyle {
+    pub notation: Notation,
+    pub radix: Radix,
+    /// Pad numbers with zeros to the width of the widest value of the type.
+    pub zero_pad: bool,
+}
+
+impl RangeStyle {
+    /// Creates a style using the given notation and unpadded decimal numbers.
+    pub fn new(notation: Notation) -> Self {
+        RangeStyle{notation, radix: Radix::Decimal, zero_pad: false}
+    }
+    /// Returns this style with numbers written in a different base.
+    pub fn radix(self, radix: Radix) -> Self {
+        RangeStyle{radix, ..self}
+    }
+    /// Returns this style with or without zero-padding of numbers.
+    pub fn zero_pad(self, zero_pad: bool) -> Self {
+        RangeStyle{zero_pad, ..self}
+    }
+}
+
+impl Default for RangeStyle {
+    fn default() -> Self {
+        RangeStyle::new(Notation::Prose)
+    }
+}
+
+/// Wrapper for displaying a single range in a given style.
+#[derive(Clone, Copy, Debug)]
+pub struct StyledRange<T: Int> {
+    range: IntRange<T>,
+    style: RangeStyle,
+}
+
+impl<T: Int> IntRange<T> {
+    /// Returns a wrapper that displays this range in the given style.
+    pub fn styled(self, style: RangeStyle) -> StyledRange<T> {
+        StyledRange{range: self, style}
+    }
+}
+
+impl<T: Int> Display for StyledRange<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        let style = self.style;
+        let number = |x| Number{value: x, style};
+        match (style.notation, self.range) {
+            // A hyphen between two characters could be a character itself.
+            (Notation::Prose, IntRange::Bound(start, end)) if T::IS_CHAR =>
+                write!(formatter, "{}..={}", number(start), number(end)),
+            (Notation::Prose, IntRange::Bound(start, end)) =>
+                write!(formatter, "{}-{}", number(start), number(end)),
+            (Notation::Prose, IntRange::To(end)) =>
+                write!(formatter, "{} and below", number(end)),
+            (Notation::Prose, IntRange::From(start)) =>
+                write!(formatter, "{} and above", number(start)),
+            (Notation::Prose, IntRange::Full) =>
+                formatter.write_str("full range"),
+            (Notation::Pattern, IntRange::Full) => formatter.write_str("_"),
+            (Notation::Pattern, range) => {
+                let (start, end) = bounds(range);
+                if start == end {
+                    write!(formatter, "{}", pattern_bound(start, style))
+                } else {
+                    write!(formatter, "{}..={}", pattern_bound(start, style),
+                           pattern_bound(end, style))
+                }
+            },
+            (Notation::Interval, range) => {
+                let (start, end) = bounds(range);
+                write!(formatter, "[{}, {}]", number(start), number(end))
+            },
+        }
+    }
+}
+
+/// Wrapper for displaying a list of ranges, e.g. `[4 and below, 7-9]`.
+#[derive(Clone, Copy, Debug)]
+pub struct RangeList<'a, T: Int + 'a> {
+    ranges: &'a [IntRange<T>],
+    style: RangeStyle,
+}
+
+impl<'a, T: Int> RangeList<'a, T> {
+    /// Creates a wrapper that displays the ranges in the default style.
+    pub fn new(ranges: &'a [IntRange<T>]) -> Self {
+        RangeList{ranges, style: RangeStyle::default()}
+    }
+    /// Returns this wrapper, displaying the ranges in a different style.
+    pub fn style(self, style: RangeStyle) -> Self {
+        RangeList{style, ..self}
+    }
+}
+
+impl<T: Int> Display for RangeList<'_, T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        let (open, separator, close, empty) = match self.style.notation {
+            Notation::Prose => ("[", ", ", "]", ""),
+            Notation::Pattern => ("", " | ", "", ""),
+            Notation::Interval => ("", " ∪ ", "", "∅"),
+        };
+        formatter.write_str(open)?;
+        if self.ranges.is_empty() {
+            formatter.write_str(empty)?;
+        }
+        let mut first = true;
+        for range in self.ranges.iter() {
+            if !first {
+                formatter.write_str(separator)?;
+            }
+            first = false;
+            write!(formatter, "{}", range.styled(self.style))?;
+        }
+        formatter.write_str(close)
+    }
+}
+
+// Get the inclusive bounds of a range.
+fn bounds<T: Int>(range: IntRange<T>) -> (T, T) {
+    match range {
+        IntRange::Bound(start, end) => (start, end),
+        IntRange::To(end) => (T::min_value(), end),
+        IntRange::From(start) => (start, T::max_value()),
+        IntRange::Full => (T::min_value(), T::max_value()),
+    }
+}
+
+// Write the extremes of a type as named constants, as in source code.
+fn pattern_bound<T: Int>(value: T, style: RangeStyle) -> PatternBound<T> {
+    PatternBound{value, style}
+}
+
+struct PatternBound<T: Int> {
+    value: T,
+    style: RangeStyle,
+}
+
+impl<T: Int> Display for PatternBound<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        // Unsigned types start at zero, which reads better as a number.
+        let negative = self.value.to_sign_magnitude().0;
+        if self.value == T::min_value() && (negative || T::IS_CHAR) {
+            write!(formatter, "{}::MIN", T::NAME)
+        } else if self.value == T::max_value() {
+            write!(formatter, "{}::MAX", T::NAME)
+        } else if T::IS_CHAR {
+            // Only literals are char patterns, whatever the radix.
+            let magnitude = self.value.to_sign_magnitude().1;
+            write_char_literal(char::from_u32(magnitude as u32).unwrap(),
+                               formatter)
+        } else {
+            let number = Number{value: self.value, style: self.style};
+            write!(formatter, "{}", number)
+        }
+    }
+}
+
+// A single number, written in the radix and padding of a style.
+struct Number<T: Int> {
+    value: T,
+    style: RangeStyle,
+}
+
+impl<T: Int> Display for Number<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        let (negative, magnitude) = self.value.to_sign_magnitude();
+        if T::IS_CHAR && self.style.radix == Radix::Decimal {
+            // Characters are valid scalar values, so this cannot fail.
+            let c = char::from_u32(magnitude as u32).unwrap();
+            return write_char(c, formatter);
+        }
+        let base = self.style.radix.base();
+        let width = if self.style.zero_pad {
+            let widest = T::min_value().to_sign_magnitude().1
+                .max(T::max_value().to_sign_magnitude().1);
+            digits(widest, base).len()
+        } else {
+            0
+        };
+        let digits = digits(magnitude, base);
+        if negative {
+            formatter.write_str("-")?;
+        }
+        formatter.write_str(self.style.radix.prefix())?;
+        for _ in digits.len()..width {
+            formatter.write_str("0")?;
+        }
+        formatter.write_str(&digits)
+    }
+}
+
+// Write a character as a quoted literal if it is printable, or as a code
+// point otherwise.
+fn write_char(c: char, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+    match c {
+        '\'' | '\\' | '"' => write_char_literal(c, formatter),
+        _ if c.escape_debug().count() == 1 => write_char_literal(c, formatter),
+        _ => write!(formatter, "U+{:04X}", c as u32),
+    }
+}
+
+// Write a character as a quoted literal, as in source code, escaping it if
+// it is not printable.
+fn write_char_literal(c: char, formatter: &mut Formatter)
+      -> Result<(), fmt::Error> {
+    match c {
+        '\'' | '\\' => write!(formatter, "'\\{}'", c),
+        '"' => formatter.write_str("'\"'"),
+        _ if c.escape_debug().count() == 1 => write!(formatter, "'{}'", c),
+        _ => write!(formatter, "'\\u{{{:X}}}'", c as u32),
+    }
+}
+
+// Write out the digits of a number in the given base.
+fn digits(mut magnitude: u128, base: u32) -> String {
+    let mut digits = Vec::new();
+    loop {
+        let digit = (magnitude % base as u128) as u32;
+        digits.push(std::char::from_digit(digit, base).unwrap());
+        magnitude /= base as u128;
+        if magnitude == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod format_tests {
+    use super::{Notation, Radix, RangeList, RangeStyle};
+    use super::super::IntRange;
+    fn pattern() -> RangeStyle {
+        RangeStyle::new(Notation::Pattern)
+    }
+    fn interval() -> RangeStyle {
+        RangeStyle::new(Notation::Interval)
+    }
+    #[test]
+    fn default_style_is_prose() {
+        let ranges = vec![IntRange::To(4i32), IntRange::Bound(7, 9),
+                          IntRange::From(20)];
+        assert_eq!(format!("{}", RangeList::new(&ranges)),
+                   "[4 and below, 7-9, 20 and above]");
+        assert_eq!(format!("{}", RangeList::new(&ranges)
+                           .style(RangeStyle::default())),
+                   "[4 and below, 7-9, 20 and above]");
+    }
+    #[test]
+    fn pattern_style() {
+        let ranges = vec![IntRange::To(-1i32), IntRange::Bound(10, 20),
+                          IntRange::Bound(25, 25), IntRange::From(30)];
+        assert_eq!(format!("{}", RangeList::new(&ranges).style(pattern())),
+                   "i32::MIN..=-1 | 10..=20 | 25 | 30..=i32::MAX");
+        assert_eq!(format!("{}", IntRange::<u8>::Full.styled(pattern())), "_");
+        assert_eq!(format!("{}", IntRange::To(5u8).styled(pattern())),
+                   "0..=5");
+    }
+    #[test]
+    fn interval_style() {
+        let ranges = vec![IntRange::To(-1i8), IntRange::Bound(10, 20)];
+        assert_eq!(format!("{}", RangeList::new(&ranges).style(interval())),
+                   "[-128, -1] ∪ [10, 20]");
+        assert_eq!(format!("{}", IntRange::<u8>::Full.styled(interval())),
+                   "[0, 255]");
+    }
+    #[test]
+    fn empty_lists() {
+        let ranges: Vec<IntRange<u8>> = vec![];
+        assert_eq!(format!("{}", RangeList::new(&ranges)), "[]");
+        assert_eq!(format!("{}", RangeList::new(&ranges).style(pattern())),
+                   "");
+        assert_eq!(format!("{}", RangeList::new(&ranges).style(interval())),
+                   "∅");
+    }
+    #[test]
+    fn radix_options() {
+        let range = IntRange::Bound(10u8, 255);
+        assert_eq!(format!("{}", range.styled(
+            RangeStyle::default().radix(Radix::Hex))), "0xa-0xff");
+        assert_eq!(format!("{}", range.styled(
+            RangeStyle::default().radix(Radix::Octal))), "0o12-0o377");
+        assert_eq!(format!("{}", range.styled(
+            RangeStyle::default().radix(Radix::Binary))),
+                   "0b1010-0b11111111");
+    }
+    #[test]
+    fn zero_padding() {
+        let style = interval().radix(Radix::Hex).zero_pad(true);
+        assert_eq!(format!("{}", IntRange::Bound(10u16, 300).styled(style)),
+                   "[0x000a, 0x012c]");
+        let style = interval().radix(Radix::Binary).zero_pad(true);
+        assert_eq!(format!("{}", IntRange::Bound(1u8, 2).styled(style)),
+                   "[0b00000001, 0b00000010]");
+        let style = interval().zero_pad(true);
+        assert_eq!(format!("{}", IntRange::Bound(-5i8, 7).styled(style)),
+                   "[-005, 007]");
+    }
+    #[test]
+    fn negative_numbers_in_radix() {
+        let style = pattern().radix(Radix::Hex);
+        assert_eq!(format!("{}", IntRange::Bound(-16i32, 16).styled(style)),
+                   "-0x10..=0x10");
+        assert_eq!(format!("{}", IntRange::From(i128::MIN + 1).styled(
+            interval().radix(Radix::Hex))),
+                   "[-0x7fffffffffffffffffffffffffffffff, \
+                    0x7fffffffffffffffffffffffffffffff]");
+    }
+    #[test]
+    fn char_ranges() {
+        assert_eq!(format!("{}", IntRange::Bound('a', 'z')), "'a'..='z'");
+        let ranges = vec![IntRange::To('\u{1F}'), IntRange::Bound('\'', '\\'),
+                          IntRange::From('\u{D7FF}')];
+        assert_eq!(format!("{}", RangeList::new(&ranges)),
+                   "[U+001F and below, '\\''..='\\\\', U+D7FF and above]");
+        assert_eq!(format!("{}", RangeList::new(&ranges).style(pattern())),
+                   "char::MIN..='\\u{1F}' | '\\''..='\\\\' | \
+                    '\\u{D7FF}'..=char::MAX");
+        assert_eq!(format!("{}", IntRange::Bound('"', 'é').styled(interval())),
+                   "['\"', 'é']");
+        assert_eq!(format!("{}", IntRange::Bound('a', 'a').styled(
+            pattern().radix(Radix::Hex))), "'a'");
+    }
+}
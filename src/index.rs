@@ -0,0 +1,157 @@
+//! An index of the original input ranges, for finding which of them contain
+//! a value.
+
+use std::cmp::max;
+use std::ops::RangeBounds;
+
+use super::{Int, MergeRange};
+use super::convert::merge_range_from_bounds;
+
+/// An immutable index over a list of ranges, answering which of them contain
+/// a value or intersect a range.
+///
+/// Unlike a `RangeSet`, the index keeps the input ranges apart, and queries
+/// return their positions in the input. The ranges are sorted by start, and
+/// each node of the implicit binary search tree over them records the largest
+/// end in its subtree. A query takes `O(log n)` time, plus `O(log n)` for each
+/// matching range.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct IntervalIndex<T: Int> {
+    // The nonempty input ranges, sorted by start, with their input indices.
+    ranges: Vec<(MergeRange<T>, usize)>,
+    // For the subtree rooted at each position, the largest end in it.
+    max_ends: Vec<T>,
+}
+
+impl<T: Int> IntervalIndex<T> {
+    /// Builds an index of the input ranges, which are accepted in the same
+    /// forms as for `uncovered_and_overlapped`. Empty ranges never match a
+    /// query, but still count toward the indices of later ranges.
+    pub fn new<I>(ranges: I) -> Self
+        where I: IntoIterator, I::Item: RangeBounds<T> {
+        let mut ranges: Vec<_> = ranges.into_iter().enumerate()
+            .filter_map(|(i, range)| {
+                merge_range_from_bounds(&range).map(|range| (range, i))
+            })
+            .collect();
+        ranges.sort_by_key(|&(range, i)| (range.start, i));
+        let mut max_ends: Vec<T> =
+            ranges.iter().map(|&(range, _)| range.end).collect();
+        build(&mut max_ends, 0, ranges.len());
+        IntervalIndex{ranges, max_ends}
+    }
+    /// Returns the indices of the input ranges containing `value`, in
+    /// ascending order.
+    pub fn stab(&self, value: T) -> Vec<usize> {
+        self.stab_range(value..=value)
+    }
+    /// Returns the indices of the input ranges that share at least one value
+    /// with `range`, in ascending order. An empty query range matches
+    /// nothing.
+    pub fn stab_range<R: RangeBounds<T>>(&self, range: R) -> Vec<usize> {
+        let mut found = Vec::new();
+        if let Some(query) = merge_range_from_bounds(&range) {
+            self.search(query, 0, self.ranges.len(), &mut found);
+        }
+        found.sort_unstable();
+        found
+    }
+    // Find the ranges intersecting `query` in the subtree over `lo..hi`.
+    fn search(&self, query: MergeRange<T>, lo: usize, hi: usize,
+              found: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        // No range in the subtree reaches the query.
+        if self.max_ends[mid] < query.start {
+            return;
+        }
+        self.search(query, lo, mid, found);
+        let (range, i) = self.ranges[mid];
+        // Ranges from here on start past the query.
+        if range.start > query.end {
+            return;
+        }
+        if range.end >= query.start {
+            found.push(i);
+        }
+        self.search(query, mid + 1, hi, found);
+    }
+}
+
+// Fill in the largest end of each subtree over `lo..hi`, returning the
+// largest for the whole of it.
+fn build<T: Int>(max_ends: &mut [T], lo: usize, hi: usize) -> Option<T> {
+    if lo >= hi {
+        return None;
+    }
+    let mid = lo + (hi - lo) / 2;
+    let mut max_end = max_ends[mid];
+    let children = [build(max_ends, lo, mid), build(max_ends, mid + 1, hi)];
+    for end in children.into_iter().flatten() {
+        max_end = max(max_end, end);
+    }
+    max_ends[mid] = max_end;
+    Some(max_end)
+}
+
+#[cfg(test)]
+mod index_tests {
+    use super::IntervalIndex;
+    use super::super::IntRange;
+    use super::super::IntRange::*;
+    use super::super::test_util::Lcg;
+    #[test]
+    fn stab_finds_original_ranges() {
+        let index = IntervalIndex::new([0u8..=50, 40..=42, 45..=60, 42..=42]);
+        assert_eq!(index.stab(42), vec![0, 1, 3]);
+        assert_eq!(index.stab(45), vec![0, 2]);
+        assert_eq!(index.stab(61), Vec::<usize>::new());
+        assert_eq!(index.stab_range(43..=44), vec![0]);
+        assert_eq!(index.stab_range(51..), vec![2]);
+        assert_eq!(index.stab_range(..), vec![0, 1, 2, 3]);
+        assert!(index.stab_range(10..10).is_empty());
+    }
+    #[test]
+    fn empty_ranges_keep_indices() {
+        let index = IntervalIndex::new([Bound(5i32, 1), Full, Bound(3, 3)]);
+        assert_eq!(index.stab(3), vec![1, 2]);
+        assert_eq!(index.stab(i32::MIN), vec![1]);
+        assert!(IntervalIndex::new(Vec::<IntRange<i32>>::new())
+                .stab(0).is_empty());
+    }
+    #[test]
+    fn matches_brute_force() {
+        let mut lcg = Lcg::new(54321);
+        let mut next = || lcg.next_u16() as i8;
+        let ranges: Vec<IntRange<i8>> = (0..100).map(|_| {
+            let (a, b) = (next(), next());
+            match a % 8 {
+                0 => To(b),
+                1 => From(b),
+                _ => Bound(a, b),
+            }
+        }).collect();
+        let index = IntervalIndex::new(&ranges);
+        let contains = |range: &IntRange<i8>, x: i8| match *range {
+            Bound(start, end) => start <= x && x <= end,
+            To(end) => x <= end,
+            From(start) => start <= x,
+            Full => true,
+        };
+        for x in i8::MIN..=i8::MAX {
+            let expected: Vec<usize> = (0..ranges.len())
+                .filter(|&i| contains(&ranges[i], x))
+                .collect();
+            assert_eq!(index.stab(x), expected);
+        }
+        for y in (i8::MIN..=i8::MAX).step_by(7) {
+            let expected: Vec<usize> = (0..ranges.len())
+                .filter(|&i| (y..=y.saturating_add(20))
+                        .any(|x| contains(&ranges[i], x)))
+                .collect();
+            assert_eq!(index.stab_range(y..=y.saturating_add(20)), expected);
+        }
+    }
+}
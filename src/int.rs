@@ -0,0 +1,249 @@
+//! The integer trait used to bound all range types in this crate.
+
+use std::fmt::Debug;
+use std::hash::Hash;
+
+pub(crate) mod sealed {
+    pub trait Sealed {
+        // True for `char`, whose values are written as characters rather
+        // than numbers.
+        const IS_CHAR: bool = false;
+    }
+}
+
+/// Primitive integer types whose ranges can be checked.
+///
+/// This trait is sealed; it is implemented for every primitive integer type
+/// and for `char`, and cannot be implemented outside of this crate.
+///
+/// The values of `char` are the Unicode scalar values, which exclude the
+/// surrogate code points `U+D800` to `U+DFFF`. The successor of `U+D7FF` is
+/// therefore `U+E000`, and the distance between them is 1.
+pub trait Int: Copy + Ord + Debug + Hash + sealed::Sealed {
+    /// The name of the type, as written in Rust source.
+    const NAME: &'static str;
+    /// The number of bits in the type.
+    const BITS: u32;
+    /// The smallest value of the type.
+    fn min_value() -> Self;
+    /// The largest value of the type.
+    fn max_value() -> Self;
+    /// The next value above this one, or `None` at the maximum.
+    fn successor(self) -> Option<Self>;
+    /// The next value below this one, or `None` at the minimum.
+    fn predecessor(self) -> Option<Self>;
+    /// The number of steps from `self` up to `other`, or `None` if `other` is
+    /// below `self`.
+    ///
+    /// The distance between the minimum and maximum of any type fits in a
+    /// `u128`, but the number of values in that range might not.
+    fn checked_distance(self, other: Self) -> Option<u128>;
+    /// Splits the value into a sign (true if negative) and a magnitude.
+    fn to_sign_magnitude(self) -> (bool, u128);
+    /// Builds a value from a sign and a magnitude, or returns `None` if it is
+    /// out of range for the type.
+    fn from_sign_magnitude(negative: bool, magnitude: u128) -> Option<Self>;
+}
+
+macro_rules! impl_int {
+    ($($t:ty => $unsigned:ty),*) => {$(
+        impl sealed::Sealed for $t {}
+
+        impl Int for $t {
+            const NAME: &'static str = stringify!($t);
+            const BITS: u32 = <$t>::BITS;
+            fn min_value() -> Self {
+                <$t>::MIN
+            }
+            fn max_value() -> Self {
+                <$t>::MAX
+            }
+            fn successor(self) -> Option<Self> {
+                self.checked_add(1)
+            }
+            fn predecessor(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+            fn checked_distance(self, other: Self) -> Option<u128> {
+                if other < self {
+                    None
+                } else {
+                    // Wrapping in the signed type and reinterpreting as the
+                    // unsigned type of the same width gives the exact
+                    // difference, even when it overflows the signed type.
+                    Some(other.wrapping_sub(self) as $unsigned as u128)
+                }
+            }
+            // The same code serves signed and unsigned types, so some of the
+            // comparisons and casts are trivial for some types.
+            #[allow(unused_comparisons, clippy::cast_lossless)]
+            fn to_sign_magnitude(self) -> (bool, u128) {
+                if self < 0 {
+                    (true, (self as $unsigned).wrapping_neg() as u128)
+                } else {
+                    (false, self as u128)
+                }
+            }
+            #[allow(unused_comparisons, clippy::cast_lossless)]
+            fn from_sign_magnitude(negative: bool, magnitude: u128)
+                  -> Option<Self> {
+                if magnitude > <$unsigned>::MAX as u128 {
+                    return None;
+                }
+                let bits = magnitude as $unsigned;
+                let value = if negative {
+                    bits.wrapping_neg() as $t
+                } else {
+                    bits as $t
+                };
+                // The value must have the requested sign, unless it is zero.
+                if magnitude != 0 && (value < 0) != negative {
+                    None
+                } else {
+                    Some(value)
+                }
+            }
+        }
+    )*}
+}
+
+impl_int! {
+    u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128,
+    usize => usize,
+    i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128,
+    isize => usize
+}
+
+impl sealed::Sealed for char {
+    const IS_CHAR: bool = true;
+}
+
+impl Int for char {
+    const NAME: &'static str = "char";
+    const BITS: u32 = 32;
+    fn min_value() -> Self {
+        char::MIN
+    }
+    fn max_value() -> Self {
+        char::MAX
+    }
+    fn successor(self) -> Option<Self> {
+        match self {
+            '\u{D7FF}' => Some('\u{E000}'),
+            char::MAX => None,
+            _ => char::from_u32(self as u32 + 1),
+        }
+    }
+    fn predecessor(self) -> Option<Self> {
+        match self {
+            '\u{E000}' => Some('\u{D7FF}'),
+            char::MIN => None,
+            _ => char::from_u32(self as u32 - 1),
+        }
+    }
+    fn checked_distance(self, other: Self) -> Option<u128> {
+        if other < self {
+            return None;
+        }
+        let mut distance = other as u32 - self as u32;
+        // Surrogates between the two values are not steps.
+        if self <= '\u{D7FF}' && other >= '\u{E000}' {
+            distance -= 0x800;
+        }
+        Some(distance as u128)
+    }
+    fn to_sign_magnitude(self) -> (bool, u128) {
+        (false, self as u128)
+    }
+    fn from_sign_magnitude(negative: bool, magnitude: u128) -> Option<Self> {
+        if negative && magnitude != 0 {
+            return None;
+        }
+        u32::try_from(magnitude).ok().and_then(char::from_u32)
+    }
+}
+
+#[cfg(test)]
+mod int_tests {
+    use super::Int;
+    #[test]
+    fn min_and_max_values() {
+        assert_eq!(<u8 as Int>::min_value(), 0u8);
+        assert_eq!(<u8 as Int>::max_value(), 255u8);
+        assert_eq!(<i128 as Int>::min_value(), i128::MIN);
+        assert_eq!(<u128 as Int>::max_value(), u128::MAX);
+    }
+    #[test]
+    fn successor_stops_at_max() {
+        assert_eq!(5i16.successor(), Some(6i16));
+        assert_eq!(i16::MAX.successor(), None);
+        assert_eq!(u128::MAX.successor(), None);
+    }
+    #[test]
+    fn predecessor_stops_at_min() {
+        assert_eq!(5usize.predecessor(), Some(4usize));
+        assert_eq!(0usize.predecessor(), None);
+        assert_eq!(i64::MIN.predecessor(), None);
+    }
+    #[test]
+    fn distance_of_ordered_values() {
+        assert_eq!(3u32.checked_distance(10), Some(7));
+        assert_eq!((-3isize).checked_distance(3), Some(6));
+        assert_eq!(7i8.checked_distance(7), Some(0));
+    }
+    #[test]
+    fn distance_of_reversed_values() {
+        assert_eq!(10u32.checked_distance(3), None);
+    }
+    #[test]
+    fn names_and_bits() {
+        assert_eq!(<u8 as Int>::NAME, "u8");
+        assert_eq!(<isize as Int>::NAME, "isize");
+        assert_eq!(<i128 as Int>::BITS, 128);
+    }
+    #[test]
+    fn sign_magnitude_round_trip() {
+        assert_eq!(5u8.to_sign_magnitude(), (false, 5));
+        assert_eq!((-5i8).to_sign_magnitude(), (true, 5));
+        assert_eq!(i8::MIN.to_sign_magnitude(), (true, 128));
+        assert_eq!(i128::MIN.to_sign_magnitude(), (true, 1u128 << 127));
+        assert_eq!(u128::MAX.to_sign_magnitude(), (false, u128::MAX));
+        assert_eq!(<i8 as Int>::from_sign_magnitude(true, 128), Some(i8::MIN));
+        assert_eq!(<i8 as Int>::from_sign_magnitude(false, 127), Some(127i8));
+        assert_eq!(<i128 as Int>::from_sign_magnitude(true, 1u128 << 127),
+                   Some(i128::MIN));
+    }
+    #[test]
+    fn sign_magnitude_out_of_range() {
+        assert_eq!(<i8 as Int>::from_sign_magnitude(false, 128), None);
+        assert_eq!(<i8 as Int>::from_sign_magnitude(true, 129), None);
+        assert_eq!(<u8 as Int>::from_sign_magnitude(false, 256), None);
+        assert_eq!(<u8 as Int>::from_sign_magnitude(true, 1), None);
+        assert_eq!(<u8 as Int>::from_sign_magnitude(true, 0), Some(0u8));
+    }
+    #[test]
+    fn distance_across_full_range() {
+        assert_eq!(i8::MIN.checked_distance(i8::MAX), Some(255));
+        assert_eq!(i128::MIN.checked_distance(i128::MAX), Some(u128::MAX));
+        assert_eq!(u128::MIN.checked_distance(u128::MAX), Some(u128::MAX));
+    }
+    #[test]
+    fn char_skips_surrogates() {
+        assert_eq!('a'.successor(), Some('b'));
+        assert_eq!('\u{D7FF}'.successor(), Some('\u{E000}'));
+        assert_eq!('\u{E000}'.predecessor(), Some('\u{D7FF}'));
+        assert_eq!(char::MAX.successor(), None);
+        assert_eq!(char::MIN.predecessor(), None);
+        assert_eq!('\u{D7FF}'.checked_distance('\u{E000}'), Some(1));
+        assert_eq!(char::MIN.checked_distance(char::MAX),
+                   Some(0x10FFFF - 0x800));
+    }
+    #[test]
+    fn char_sign_magnitude() {
+        assert_eq!('a'.to_sign_magnitude(), (false, 97));
+        assert_eq!(<char as Int>::from_sign_magnitude(false, 97), Some('a'));
+        assert_eq!(<char as Int>::from_sign_magnitude(false, 0xD800), None);
+        assert_eq!(<char as Int>::from_sign_magnitude(false, 0x110000), None);
+        assert_eq!(<char as Int>::from_sign_magnitude(true, 1), None);
+    }
+}
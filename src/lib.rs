@@ -1,13 +1,63 @@
-/// Range checking utility for Rust integer types.
-#![crate_name(int_range_check)]
-#![crate_type="lib"]
+//! Range checking utility for Rust integer types.
+#![crate_name = "int_range_check"]
+#![crate_type = "lib"]
 
-use std::cmp::{min, max};
+use std::cmp::{min, max, Ordering};
 use std::fmt::{self, Display, Formatter};
-use std::num::Int;
+use std::ops::{BitAnd, BitOr, BitXor, Not, RangeBounds, Sub};
+
+pub use arms::{ArmReport, Guard, MatchAnalysis, Reachability};
+pub use boxes::{RangeBox, uncovered_and_overlapped_boxes};
+pub use convert::EmptyRangeError;
+pub use coverage::{CoverageMap, Segments};
+pub use domain::{RangeDomain, TableCheck};
+pub use format::{Notation, Radix, RangeList, RangeStyle, StyledRange};
+pub use index::IntervalIndex;
+pub use int::Int;
+#[cfg(feature = "derive")]
+pub use int_range_check_macros::RangeDomain;
+pub use map::{Entries, RangeMap};
+pub use mask::{MaskLimitError, MaskPattern, masks_from_ranges,
+               masks_to_ranges, uncovered_and_overlapped_masks};
+pub use parse::{ParseErrorKind, ParseRangeError, parse_ranges};
+pub use provenance::{Provenance, overlap_provenance,
+                     overlap_provenance_by_key};
+pub use report::CheckReport;
+pub use strided::{CongruenceSet, StrideLimitError, StridedRange,
+                  uncovered_and_overlapped_strided};
+pub use validate::{InvalidRange, InvalidRangeKind, Validation,
+                   ValidationError, checked_uncovered_and_overlapped,
+                   validate_ranges};
 
 use self::MergeResult::*;
 
+// Lets derived code name this crate from inside it, in tests.
+#[cfg(test)]
+extern crate self as int_range_check;
+
+mod arms;
+mod boxes;
+pub mod const_eval;
+mod convert;
+mod coverage;
+mod domain;
+mod format;
+mod index;
+mod int;
+#[cfg(feature = "lint")]
+pub mod lint;
+mod map;
+mod mask;
+mod parse;
+mod provenance;
+mod report;
+#[cfg(feature = "serde")]
+pub mod serde_forms;
+mod strided;
+#[cfg(test)]
+mod test_util;
+mod validate;
+
 /// Returns:
 ///
 ///  1) a vector containing the ranges representable by the integer type which
@@ -18,24 +68,99 @@ use self::MergeResult::*;
 ///
 /// If the former is empty, then the input ranges are exhaustive. If the latter
 /// is empty, then they have no overlap.
-pub fn uncovered_and_overlapped<T: Int>(ranges: &Vec<IntRange<T>>)
-      -> (Vec<IntRange<T>>, Vec<IntRange<T>>) {
+///
+/// To find out how many input ranges cover each overlapped value, use a
+/// `CoverageMap` instead. To find out which input ranges overlap, use
+/// `overlap_provenance`. To avoid allocating the output vectors, use
+/// `RangeCheck`.
+///
+/// The input may be any iterable of `IntRange`s, references to `IntRange`s,
+/// or any of the standard range types, such as `a..=b` or `a..b`. Empty
+/// ranges are ignored; use `checked_uncovered_and_overlapped` to have them
+/// reported instead.
+pub fn uncovered_and_overlapped<T, I>(ranges: I)
+      -> (Vec<IntRange<T>>, Vec<IntRange<T>>)
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
+    let check = RangeCheck::new(ranges);
+    (check.uncovered().collect(), check.overlapped().ranges())
+}
+
+/// Result of checking a list of ranges for exhaustiveness and overlap.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RangeCheck<T: Int> {
+    covered: RangeSet<T>,
+    overlapped: RangeSet<T>,
+}
+
+impl<T: Int> RangeCheck<T> {
+    /// Checks the input ranges. The input is consumed one range at a time,
+    /// so it need not be collected first; see `uncovered_and_overlapped` for
+    /// the types of range accepted.
+    pub fn new<I>(ranges: I) -> Self
+        where I: IntoIterator, I::Item: RangeBounds<T> {
+        let (covered, overlapped) =
+            RangeSet::from_vec_with_overlap(convert::merge_ranges(ranges));
+        RangeCheck{covered, overlapped}
+    }
+    /// Returns the set of values covered by at least one input range.
+    pub fn covered(&self) -> &RangeSet<T> {
+        &self.covered
+    }
+    /// Returns the set of values covered by more than one input range.
+    pub fn overlapped(&self) -> &RangeSet<T> {
+        &self.overlapped
+    }
+    /// Iterates over the ranges not covered by any input range.
+    pub fn uncovered(&self) -> Gaps<'_, T> {
+        self.covered.gaps()
+    }
+    /// Returns true if every value of the type is covered.
+    pub fn is_exhaustive(&self) -> bool {
+        self.covered.is_full()
+    }
+    /// Returns true if no value is covered more than once.
+    pub fn is_disjoint(&self) -> bool {
+        self.overlapped.is_empty()
+    }
+}
+
+/// Result of checking ranges against a restricted domain.
+///
+/// All three sets are disjoint from one another. `uncovered` and `overlapped`
+/// are subsets of the domain, while `outside` contains the input values that
+/// fall outside of it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DomainCheck<T: Int> {
+    /// Values in the domain that are not covered by any input range.
+    pub uncovered: RangeSet<T>,
+    /// Values in the domain that are covered by more than one input range.
+    pub overlapped: RangeSet<T>,
+    /// Values outside of the domain that are covered by some input range.
+    pub outside: RangeSet<T>,
+}
+
+/// Like `uncovered_and_overlapped`, but measures coverage against `domain`
+/// rather than the whole range of the integer type.
+///
+/// Overlap outside of the domain is only reported as part of `outside`.
+pub fn check_in_domain<T, I>(ranges: I, domain: &RangeSet<T>)
+      -> DomainCheck<T>
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
     let (range_set, overlap_set) =
-        RangeSet::from_vec_with_overlap(&(
-            ranges.iter().filter_map(|&x| x.to_merge_range()).collect()
-                ));
-    let uncovered_set = range_set.complement();
-    (uncovered_set.into_vec().iter()
-         .map(|&x| IntRange::from_merge_range(x)).collect(),
-     overlap_set.into_vec().iter()
-         .map(|&x| IntRange::from_merge_range(x)).collect())
+        RangeSet::from_vec_with_overlap(convert::merge_ranges(ranges));
+    DomainCheck{
+        uncovered: range_set.complement_in(domain),
+        overlapped: overlap_set.intersection(domain),
+        outside: range_set.difference(domain),
+    }
 }
 
 /// Representation of inclusive integer ranges.
 ///
 /// `To`, `From`, and `Full` are the inclusive equivalents of the associated
 /// `Range` types. `Bound` is the equivalent of `Range` itself.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum IntRange<T: Int> {
     Bound(T, T),
     To(T),
@@ -57,47 +182,23 @@ impl<T: Int> IntRange<T> {
         }
     }
     fn from_merge_range(merge_range: MergeRange<T>) -> Self {
-        if merge_range.start > (<T as Int>::min_value()) {
-            if merge_range.end < (<T as Int>::max_value()) {
+        if merge_range.start > T::min_value() {
+            if merge_range.end < T::max_value() {
                 IntRange::Bound(merge_range.start, merge_range.end)
             } else {
                 IntRange::From(merge_range.start)
             }
+        } else if merge_range.end < T::max_value() {
+            IntRange::To(merge_range.end)
         } else {
-            if merge_range.end < (<T as Int>::max_value()) {
-                IntRange::To(merge_range.end)
-            } else {
-                IntRange::Full
-            }
+            IntRange::Full
         }
     }
 }
 
-impl<T: Display+Int> Display for IntRange<T> {
+impl<T: Int> Display for IntRange<T> {
     fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
-        let output = match *self {
-            IntRange::Bound(start, end) => format!("{}-{}", start, end),
-            IntRange::To(end) => format!("{} and below", end),
-            IntRange::From(start) => format!("{} and above", start),
-            IntRange::Full => format!("{}", "full range")
-        };
-        formatter.write_str(&*output)
-    }
-}
-
-impl<T: Display+Int> Display for Vec<IntRange<T>> {
-    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
-        try!(formatter.write_str("["));
-        let mut first = true;
-        for range in self.iter() {
-            if !first {
-                try!(formatter.write_fmt(format_args!(", {}", range)));
-            } else {
-                first = false;
-                try!(formatter.write_fmt(format_args!("{}", range)));
-            }
-        }
-        formatter.write_str("]")
+        Display::fmt(&self.styled(RangeStyle::default()), formatter)
     }
 }
 
@@ -105,6 +206,9 @@ impl<T: Display+Int> Display for Vec<IntRange<T>> {
 mod interface_tests {
     use super::IntRange;
     use super::MergeRange;
+    use super::RangeList;
+    use super::{RangeCheck, RangeSet};
+    use super::{check_in_domain, uncovered_and_overlapped};
     #[test]
     fn bound_convert_merge_range() {
         assert_eq!(IntRange::Bound(2u8, 5u8).to_merge_range(),
@@ -177,36 +281,318 @@ mod interface_tests {
             IntRange::To(4u8),
             IntRange::Bound(7u8, 9u8),
             ];
-        assert_eq!(format!("{}", int_range_vec), "[4 and below, 7-9]")
+        assert_eq!(format!("{}", RangeList::new(&int_range_vec)),
+                   "[4 and below, 7-9]")
+    }
+    #[test]
+    fn static_table_checked_in_place() {
+        static TABLE: [IntRange<u8>; 3] = [
+            IntRange::To(9),
+            IntRange::Bound(10, 99),
+            IntRange::Bound(90, 200),
+            ];
+        let check = RangeCheck::new(TABLE.iter());
+        assert!(!check.is_exhaustive());
+        assert!(!check.is_disjoint());
+        assert_eq!(check.uncovered().collect::<Vec<_>>(),
+                   vec![IntRange::From(201u8)]);
+        assert_eq!(check.overlapped().ranges(),
+                   vec![IntRange::Bound(90u8, 99)]);
+        assert_eq!(check.covered().ranges(), vec![IntRange::To(200u8)]);
+        assert_eq!(uncovered_and_overlapped(TABLE.iter()),
+                   (vec![IntRange::From(201u8)],
+                    vec![IntRange::Bound(90u8, 99)]));
+    }
+    #[test]
+    fn iterator_input_checked() {
+        let input = "0-9 10-19 15-255";
+        let ranges = input.split(' ').map(|word| {
+            let mut bounds = word.split('-').map(|x| x.parse::<u8>().unwrap());
+            IntRange::Bound(bounds.next().unwrap(), bounds.next().unwrap())
+        });
+        let check = RangeCheck::new(ranges);
+        assert!(check.is_exhaustive());
+        assert_eq!(check.uncovered().next(), None);
+        assert_eq!(check.overlapped().ranges(),
+                   vec![IntRange::Bound(15u8, 19)]);
+    }
+    #[test]
+    fn exhaustive_disjoint_check() {
+        let check = RangeCheck::new(vec![IntRange::To(-1i64),
+                                         IntRange::From(0i64)]);
+        assert!(check.is_exhaustive());
+        assert!(check.is_disjoint());
+        let check = RangeCheck::<i64>::new(Vec::<IntRange<i64>>::new());
+        assert!(!check.is_exhaustive());
+        assert!(check.is_disjoint());
+        assert_eq!(check.uncovered().collect::<Vec<_>>(),
+                   vec![IntRange::Full]);
+    }
+    #[test]
+    fn domain_check_restricts_uncovered() {
+        let domain: RangeSet<u16> = IntRange::Bound(0, 999).into();
+        let check = check_in_domain([
+            IntRange::Bound(100u16, 299),
+            IntRange::Bound(200u16, 599),
+            ], &domain);
+        assert_eq!(check.uncovered.ranges(),
+                   vec![IntRange::To(99u16), IntRange::Bound(600, 999)]);
+        assert_eq!(check.overlapped.ranges(),
+                   vec![IntRange::Bound(200u16, 299)]);
+        assert!(check.outside.is_empty());
+    }
+    #[test]
+    fn domain_check_reports_outside() {
+        let domain: RangeSet<i32> =
+            vec![IntRange::Bound(0, 9), IntRange::Bound(20, 29)]
+            .into_iter().collect();
+        let check = check_in_domain([
+            IntRange::To(9i32),
+            IntRange::Bound(15i32, 40),
+            IntRange::Bound(35i32, 50),
+            ], &domain);
+        assert!(check.uncovered.is_empty());
+        // The overlap at 35-40 is outside of the domain.
+        assert!(check.overlapped.is_empty());
+        assert_eq!(check.outside.ranges(), vec![
+            IntRange::To(-1i32),
+            IntRange::Bound(15i32, 19),
+            IntRange::Bound(30i32, 50),
+            ]);
+    }
+    #[test]
+    fn full_domain_matches_uncovered_and_overlapped() {
+        let ranges = vec![IntRange::Bound(0i8, 5), IntRange::From(3)];
+        let (uncovered, overlapped) = uncovered_and_overlapped(&ranges);
+        let check = check_in_domain(&ranges, &RangeSet::full());
+        assert_eq!(check.uncovered.ranges(), uncovered);
+        assert_eq!(check.overlapped.ranges(), overlapped);
+        assert!(check.outside.is_empty());
+    }
+    #[test]
+    fn empty_domain_makes_everything_outside() {
+        let check = check_in_domain([IntRange::Bound(1u8, 2)],
+                                    &RangeSet::new());
+        assert!(check.uncovered.is_empty());
+        assert_eq!(check.outside.ranges(), vec![IntRange::Bound(1u8, 2)]);
+    }
+    #[test]
+    fn wide_types_checked() {
+        let (uncovered, overlapped) = uncovered_and_overlapped([
+            IntRange::To(-1i128),
+            IntRange::Bound(0i128, 10),
+            IntRange::From(5i128),
+            ]);
+        assert_eq!(uncovered, vec![]);
+        assert_eq!(overlapped, vec![IntRange::Bound(5i128, 10)]);
+        let (uncovered, overlapped) = uncovered_and_overlapped([
+            IntRange::From(1u128),
+            ]);
+        assert_eq!(uncovered, vec![IntRange::To(0u128)]);
+        assert_eq!(overlapped, vec![]);
+    }
+    #[test]
+    fn pointer_sized_types_checked() {
+        let (uncovered, _) = uncovered_and_overlapped([
+            IntRange::Bound(0usize, 10),
+            ]);
+        assert_eq!(uncovered, vec![IntRange::From(11usize)]);
+        let (uncovered, _) = uncovered_and_overlapped([
+            IntRange::Full::<isize>,
+            ]);
+        assert_eq!(uncovered, vec![]);
+    }
+    #[test]
+    fn char_ranges_skip_surrogates() {
+        let (uncovered, overlapped) = uncovered_and_overlapped([
+            '\0'..='\u{D7FF}', '\u{E000}'..=char::MAX]);
+        assert_eq!(uncovered, vec![]);
+        assert_eq!(overlapped, vec![]);
+        let letters = RangeSet::from(IntRange::Bound('a', 'z'));
+        assert_eq!(letters.complement().ranges(), vec![
+            IntRange::To('`'), IntRange::From('{')]);
+        assert_eq!(uncovered_and_overlapped([..'\u{E000}']).0,
+                   vec![IntRange::From('\u{E000}')]);
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
-struct RangeSet<T: Int> {
+/// A set of integers, stored as a sorted list of disjoint, non-adjacent
+/// ranges.
+///
+/// Sets support the usual set algebra, either through methods or through the
+/// operators `|` (union), `&` (intersection), `-` (difference), `^`
+/// (symmetric difference) and `!` (complement over the whole type).
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RangeSet<T: Int> {
     ranges: Vec<MergeRange<T>>,
 }
 
 impl<T: Int> RangeSet<T> {
-    fn new() -> Self {
+    /// Creates an empty set.
+    pub fn new() -> Self {
         RangeSet{ranges: Vec::new()}
     }
+    /// Creates a set containing every value of the type.
+    pub fn full() -> Self {
+        RangeSet{ranges: vec![MergeRange::range_full()]}
+    }
+    /// Returns true if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    /// Returns true if the set contains every value of the type.
+    pub fn is_full(&self) -> bool {
+        self.ranges == [MergeRange::range_full()]
+    }
+    /// Returns true if `value` is in the set.
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges.binary_search_by(|range| {
+            if range.end < value {
+                Ordering::Less
+            } else if range.start > value {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        }).is_ok()
+    }
+    /// Adds a range to the set. Empty `Bound` ranges are ignored.
+    pub fn insert(&mut self, range: IntRange<T>) {
+        if let Some(merge_range) = range.to_merge_range() {
+            self.push(merge_range);
+        }
+    }
+    /// Returns the disjoint ranges making up the set, in ascending order.
+    pub fn ranges(&self) -> Vec<IntRange<T>> {
+        self.iter().collect()
+    }
+    /// Iterates over the disjoint ranges making up the set, in ascending
+    /// order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter{inner: self.ranges.iter()}
+    }
+    /// Returns the set of values in either `self` or `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        // Merge the two sorted lists, then coalesce neighbors.
+        let mut union_set = RangeSet{
+            ranges: Vec::with_capacity(self.ranges.len() + other.ranges.len()),
+        };
+        let mut left = self.ranges.iter().peekable();
+        let mut right = other.ranges.iter().peekable();
+        loop {
+            let next = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => if l.start <= r.start {
+                    left.next()
+                } else {
+                    right.next()
+                },
+                (Some(_), None) => left.next(),
+                (None, _) => right.next(),
+            };
+            match next {
+                Some(&range) => union_set.push_last(range),
+                None => break,
+            }
+        }
+        union_set
+    }
+    /// Returns the set of values in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intersection_set = RangeSet::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (x, y) = (self.ranges[i], other.ranges[j]);
+            if let Overlap(_, overlap) = x.merge(y) {
+                intersection_set.ranges.push(overlap);
+            }
+            // Advance past whichever range ends first; it cannot overlap
+            // anything further along in the other set.
+            if x.end < y.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        intersection_set
+    }
+    /// Returns the set of values in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+    /// Returns the set of values in exactly one of `self` and `other`.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.difference(other).union(&other.difference(self))
+    }
+    /// Returns true if every value in `self` is also in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+    /// Returns true if every value in `other` is also in `self`.
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+    /// Returns true if `self` and `other` have no values in common.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).is_empty()
+    }
     #[cfg(test)]
-    fn from_vec(v: &Vec<MergeRange<T>>) -> Self {
+    fn from_vec(v: &[MergeRange<T>]) -> Self {
         let mut range_set = RangeSet::new();
         for &range in v.iter() { range_set.push(range); }
         range_set
     }
-    fn from_vec_with_overlap(v: &Vec<MergeRange<T>>) -> (Self, Self) {
+    // Build the union and the overlap of a batch of ranges. This gives the
+    // same result as calling `push_with_overlap` on each range in turn, but
+    // sorts the ranges first so that a single sweep suffices. The sort is
+    // linear on input that is already sorted.
+    fn from_vec_with_overlap(mut v: Vec<MergeRange<T>>) -> (Self, Self) {
+        v.sort_by_key(|range| range.start);
+        RangeSet::sweep_with_overlap(v)
+    }
+    // Sweep over ranges in order of their start points. Each new range can
+    // only overlap or touch the last range in the union, and any overlap
+    // starts no earlier than previously found overlaps, so both sets are
+    // built by appending.
+    fn sweep_with_overlap<I>(sorted: I) -> (Self, Self)
+        where I: IntoIterator<Item=MergeRange<T>> {
         let mut range_set = RangeSet::new();
         let mut overlap_set = RangeSet::new();
-        for &range in v.iter() {
-            range_set.push_with_overlap(&mut overlap_set, range);
+        let mut sorted = sorted.into_iter();
+        let mut current = match sorted.next() {
+            Some(range) => range,
+            None => return (range_set, overlap_set),
+        };
+        for range in sorted {
+            debug_assert!(current.start <= range.start);
+            match current.merge(range) {
+                Separate => {
+                    range_set.ranges.push(current);
+                    current = range;
+                },
+                Adjacent(concat) => current = concat,
+                Overlap(union, overlap) => {
+                    current = union;
+                    overlap_set.push_last(overlap);
+                },
+            }
         }
+        range_set.ranges.push(current);
         (range_set, overlap_set)
     }
+    #[cfg(test)]
     fn into_vec(self) -> Vec<MergeRange<T>> {
         self.ranges
     }
+    // Push a range that starts no earlier than any range already in the set.
+    fn push_last(&mut self, push_range: MergeRange<T>) {
+        match self.ranges.last_mut() {
+            Some(last) => match last.merge(push_range) {
+                Separate => self.ranges.push(push_range),
+                Adjacent(union) | Overlap(union, _) => *last = union,
+            },
+            None => self.ranges.push(push_range),
+        }
+    }
     fn push(&mut self, push_range: MergeRange<T>) {
         let mut overlap_set = RangeSet::new();
         self.push_with_overlap(&mut overlap_set, push_range);
@@ -216,7 +602,7 @@ impl<T: Int> RangeSet<T> {
         let mut new_ranges = Vec::with_capacity(self.ranges.len() + 1);
         {
             // Drain the original range vector to create the new one.
-            let mut range_iter = self.ranges.drain();
+            let mut range_iter = self.ranges.drain(..);
             let mut new_range = push_range;
             loop {
                 match range_iter.next() {
@@ -247,7 +633,18 @@ impl<T: Int> RangeSet<T> {
         }
         self.ranges = new_ranges;
     }
-    fn complement(&self) -> Self {
+    /// Iterates over the ranges of values that are not in the set, in
+    /// ascending order. This yields the same ranges as `complement`, without
+    /// allocating a new set.
+    pub fn gaps(&self) -> Gaps<'_, T> {
+        Gaps{ranges: &self.ranges, next_start: Some(T::min_value())}
+    }
+    /// Returns the set of values in `domain` that are not in `self`.
+    pub fn complement_in(&self, domain: &Self) -> Self {
+        domain.difference(self)
+    }
+    /// Returns the set of values of the type that are not in `self`.
+    pub fn complement(&self) -> Self {
         let mut complement_set = RangeSet::new();
         let len = self.ranges.len();
         // Treat an empty RangeSet specially.
@@ -255,35 +652,185 @@ impl<T: Int> RangeSet<T> {
             complement_set.push(MergeRange::range_full());
             return complement_set;
         }
-        // This is needed because a literal "1" can't be coerced to a "T".
-        let one = <T as Int>::one();
         // Get the gap on the left boundary, if any.
-        if self.ranges[0].start > (<T as Int>::min_value()) {
-            complement_set.push(
-                MergeRange::from_range_to(self.ranges[0].start - one)
-                    );
+        if let Some(end) = self.ranges[0].start.predecessor() {
+            complement_set.push(MergeRange::from_range_to(end));
         }
-        // Get the gaps between ranges.
+        // Get the gaps between ranges. Ranges in the set are never adjacent,
+        // so there is always at least one value in each gap.
         for i in 1..len {
             complement_set.push(
-                MergeRange::from_range(self.ranges[i-1].end + one,
-                                       self.ranges[i].start - one)
+                MergeRange::from_range(
+                    self.ranges[i-1].end.successor().unwrap(),
+                    self.ranges[i].start.predecessor().unwrap())
                 );
         }
         // Get the right boundary gap, if any.
-        if self.ranges[len-1].end < (<T as Int>::max_value()) {
-            complement_set.push(
-                MergeRange::from_range_from(self.ranges[len-1].end + one)
-                    );
+        if let Some(start) = self.ranges[len-1].end.successor() {
+            complement_set.push(MergeRange::from_range_from(start));
         }
         complement_set
     }
 }
 
+impl<T: Int> Display for RangeSet<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        Display::fmt(&RangeList::new(&self.ranges()), formatter)
+    }
+}
+
+impl<T: Int> Default for RangeSet<T> {
+    fn default() -> Self {
+        RangeSet::new()
+    }
+}
+
+impl<T: Int> From<IntRange<T>> for RangeSet<T> {
+    fn from(range: IntRange<T>) -> Self {
+        let mut range_set = RangeSet::new();
+        range_set.insert(range);
+        range_set
+    }
+}
+
+impl<T: Int> FromIterator<IntRange<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item=IntRange<T>>>(iter: I) -> Self {
+        let merge_ranges: Vec<_> = iter.into_iter()
+            .filter_map(|x| x.to_merge_range()).collect();
+        RangeSet::from_vec_with_overlap(merge_ranges).0
+    }
+}
+
+impl<T: Int> Extend<IntRange<T>> for RangeSet<T> {
+    fn extend<I: IntoIterator<Item=IntRange<T>>>(&mut self, iter: I) {
+        *self = self.union(&iter.into_iter().collect());
+    }
+}
+
+impl<'a, T: Int> IntoIterator for &'a RangeSet<T> {
+    type Item = IntRange<T>;
+    type IntoIter = Iter<'a, T>;
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator over the ranges in a `RangeSet`.
+#[derive(Clone, Debug)]
+pub struct Iter<'a, T: Int + 'a> {
+    inner: std::slice::Iter<'a, MergeRange<T>>,
+}
+
+impl<T: Int> Iterator for Iter<'_, T> {
+    type Item = IntRange<T>;
+    fn next(&mut self) -> Option<IntRange<T>> {
+        self.inner.next().map(|&x| IntRange::from_merge_range(x))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Int> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<IntRange<T>> {
+        self.inner.next_back().map(|&x| IntRange::from_merge_range(x))
+    }
+}
+
+impl<T: Int> ExactSizeIterator for Iter<'_, T> {}
+
+/// Iterator over the gaps between the ranges in a `RangeSet`.
+#[derive(Clone, Debug)]
+pub struct Gaps<'a, T: Int + 'a> {
+    ranges: &'a [MergeRange<T>],
+    // Start of the next gap, or `None` once the maximum has been passed.
+    next_start: Option<T>,
+}
+
+impl<T: Int> Iterator for Gaps<'_, T> {
+    type Item = IntRange<T>;
+    fn next(&mut self) -> Option<IntRange<T>> {
+        loop {
+            let start = self.next_start?;
+            match self.ranges.split_first() {
+                Some((range, rest)) => {
+                    self.ranges = rest;
+                    self.next_start = range.end.successor();
+                    // The first range may start at the minimum, leaving no
+                    // gap before it.
+                    if let Some(end) = range.start.predecessor() {
+                        if start <= end {
+                            return Some(IntRange::from_merge_range(
+                                MergeRange::from_range(start, end)));
+                        }
+                    }
+                },
+                None => {
+                    self.next_start = None;
+                    return Some(IntRange::from_merge_range(
+                        MergeRange::from_range_from(start)));
+                },
+            }
+        }
+    }
+}
+
+// Implement a binary operator for all combinations of owned and borrowed
+// sets, by forwarding to the named method.
+macro_rules! range_set_binop {
+    ($op_trait:ident, $op_fn:ident, $method:ident) => {
+        impl<'a, 'b, T: Int> $op_trait<&'b RangeSet<T>> for &'a RangeSet<T> {
+            type Output = RangeSet<T>;
+            fn $op_fn(self, other: &'b RangeSet<T>) -> RangeSet<T> {
+                self.$method(other)
+            }
+        }
+        impl<'a, T: Int> $op_trait<RangeSet<T>> for &'a RangeSet<T> {
+            type Output = RangeSet<T>;
+            fn $op_fn(self, other: RangeSet<T>) -> RangeSet<T> {
+                self.$method(&other)
+            }
+        }
+        impl<'b, T: Int> $op_trait<&'b RangeSet<T>> for RangeSet<T> {
+            type Output = RangeSet<T>;
+            fn $op_fn(self, other: &'b RangeSet<T>) -> RangeSet<T> {
+                self.$method(other)
+            }
+        }
+        impl<T: Int> $op_trait<RangeSet<T>> for RangeSet<T> {
+            type Output = RangeSet<T>;
+            fn $op_fn(self, other: RangeSet<T>) -> RangeSet<T> {
+                self.$method(&other)
+            }
+        }
+    }
+}
+
+range_set_binop!(BitOr, bitor, union);
+range_set_binop!(BitAnd, bitand, intersection);
+range_set_binop!(Sub, sub, difference);
+range_set_binop!(BitXor, bitxor, symmetric_difference);
+
+impl<T: Int> Not for &RangeSet<T> {
+    type Output = RangeSet<T>;
+    fn not(self) -> RangeSet<T> {
+        self.complement()
+    }
+}
+
+impl<T: Int> Not for RangeSet<T> {
+    type Output = RangeSet<T>;
+    fn not(self) -> RangeSet<T> {
+        self.complement()
+    }
+}
+
 #[cfg(test)]
 mod range_set_tests {
+    use super::IntRange;
     use super::RangeSet;
     use super::MergeRange;
+    use super::test_util::Lcg;
     #[test]
     fn new_is_empty() {
         assert_eq!(RangeSet::<i16>::new().into_vec(), Vec::new());
@@ -351,7 +898,7 @@ mod range_set_tests {
             MergeRange::from_range(4i64, 7),
             ];
         let mut push_range_set = RangeSet::new();
-        range_vec.iter().map(|x| push_range_set.push((*x).clone())).last();
+        for &range in range_vec.iter() { push_range_set.push(range); }
 
         let vec_range_set = RangeSet::from_vec(&range_vec);
         assert_eq!(vec_range_set, push_range_set);
@@ -391,10 +938,53 @@ mod range_set_tests {
             ];
 
         let (range_set, overlap_set) =
-            RangeSet::from_vec_with_overlap(&range_vec);
+            RangeSet::from_vec_with_overlap(range_vec.clone());
         assert_eq!(range_set, RangeSet::from_vec(&range_vec));
         assert_eq!(overlap_set, RangeSet::from_vec(&overlap_vec));
     }
+    // Compare the batch sweep against pushing ranges one at a time.
+    fn check_sweep_matches_push(range_vec: &[MergeRange<i16>]) {
+        let mut range_set = RangeSet::new();
+        let mut overlap_set = RangeSet::new();
+        for &range in range_vec.iter() {
+            range_set.push_with_overlap(&mut overlap_set, range);
+        }
+        assert_eq!(RangeSet::from_vec_with_overlap(range_vec.to_vec()),
+                   (range_set, overlap_set));
+    }
+    #[test]
+    fn sweep_matches_push_on_empty_input() {
+        check_sweep_matches_push(&[]);
+    }
+    #[test]
+    fn sweep_matches_push_on_pseudorandom_input() {
+        let mut lcg = Lcg::new(12345);
+        let mut next = || lcg.next_u16() as i16;
+        for len in [1usize, 2, 5, 20, 200].iter() {
+            let range_vec: Vec<_> = (0..*len).map(|_| {
+                let (a, b) = (next(), next() % 500);
+                MergeRange::from_range(a, a.saturating_add(b.abs()))
+            }).collect();
+            check_sweep_matches_push(&range_vec);
+        }
+    }
+    #[test]
+    fn sweep_matches_push_on_sorted_input() {
+        let range_vec: Vec<_> = (0i16..100).map(|i| {
+            MergeRange::from_range(i * 10, i * 10 + (i % 4) * 5)
+        }).collect();
+        check_sweep_matches_push(&range_vec);
+    }
+    #[test]
+    fn sweep_matches_push_at_type_limits() {
+        check_sweep_matches_push(&[
+            MergeRange::from_range_from(0i16),
+            MergeRange::range_full(),
+            MergeRange::from_range_to(-1i16),
+            MergeRange::from_range_from(i16::MAX),
+            MergeRange::from_range_to(i16::MIN),
+            ]);
+    }
     #[test]
     fn complement_yields_correct_set() {
         let range_vec = vec![
@@ -415,9 +1005,145 @@ mod range_set_tests {
         assert_eq!(range_set.complement(), RangeSet::from_vec(&range_full_vec));
         assert_eq!(range_set.complement().complement(), range_set);
     }
+    #[test]
+    fn full_is_full() {
+        assert!(RangeSet::<i8>::full().is_full());
+        assert!(!RangeSet::<i8>::new().is_full());
+        assert!(RangeSet::<i8>::new().is_empty());
+        assert_eq!(RangeSet::<i8>::full(), !RangeSet::new());
+    }
+    #[test]
+    fn insert_ignores_empty_bound() {
+        let mut range_set = RangeSet::new();
+        range_set.insert(IntRange::Bound(5u8, 1));
+        assert!(range_set.is_empty());
+        range_set.insert(IntRange::To(3u8));
+        assert_eq!(range_set.ranges(), vec![IntRange::To(3u8)]);
+    }
+    #[test]
+    fn contains_values_in_ranges() {
+        let range_set: RangeSet<i32> =
+            vec![IntRange::To(-10), IntRange::Bound(0, 5), IntRange::From(20)]
+            .into_iter().collect();
+        for &x in [i32::MIN, -10, 0, 3, 5, 20, i32::MAX].iter() {
+            assert!(range_set.contains(x), "{} should be in the set", x);
+        }
+        for &x in [-9, -1, 6, 19].iter() {
+            assert!(!range_set.contains(x), "{} should not be in the set", x);
+        }
+    }
+    #[test]
+    fn iter_yields_int_ranges() {
+        let range_set: RangeSet<u8> =
+            vec![IntRange::Bound(7, 9), IntRange::To(4)].into_iter().collect();
+        assert_eq!(range_set.iter().collect::<Vec<_>>(),
+                   vec![IntRange::To(4), IntRange::Bound(7, 9)]);
+        assert_eq!(range_set.iter().next_back(), Some(IntRange::Bound(7, 9)));
+        assert_eq!(format!("{}", range_set), "[4 and below, 7-9]");
+    }
+    #[test]
+    fn union_combines_sets() {
+        let x: RangeSet<u8> =
+            vec![IntRange::Bound(0, 5), IntRange::Bound(20, 30)]
+            .into_iter().collect();
+        let y: RangeSet<u8> =
+            vec![IntRange::Bound(6, 10), IntRange::Bound(25, 40),
+                 IntRange::From(200)]
+            .into_iter().collect();
+        let expected: RangeSet<u8> =
+            vec![IntRange::Bound(0, 10), IntRange::Bound(20, 40),
+                 IntRange::From(200)]
+            .into_iter().collect();
+        assert_eq!(x.union(&y), expected);
+        assert_eq!(&y | &x, expected);
+        assert_eq!(x.clone() | RangeSet::new(), x);
+    }
+    #[test]
+    fn intersection_keeps_common_values() {
+        let x: RangeSet<i16> =
+            vec![IntRange::To(5), IntRange::Bound(20, 30)]
+            .into_iter().collect();
+        let y: RangeSet<i16> =
+            vec![IntRange::Bound(-3, 0), IntRange::Bound(4, 25)]
+            .into_iter().collect();
+        let expected: RangeSet<i16> =
+            vec![IntRange::Bound(-3, 0), IntRange::Bound(4, 5),
+                 IntRange::Bound(20, 25)]
+            .into_iter().collect();
+        assert_eq!(x.intersection(&y), expected);
+        assert_eq!(&y & &x, expected);
+        assert_eq!(x.clone() & RangeSet::full(), x);
+    }
+    #[test]
+    fn difference_removes_values() {
+        let x: RangeSet<u32> = IntRange::Bound(10, 20).into();
+        let y: RangeSet<u32> =
+            vec![IntRange::Bound(12, 13), IntRange::From(18)]
+            .into_iter().collect();
+        let expected: RangeSet<u32> =
+            vec![IntRange::Bound(10, 11), IntRange::Bound(14, 17)]
+            .into_iter().collect();
+        assert_eq!(x.difference(&y), expected);
+        assert_eq!(&x - &y, expected);
+        assert!((y - x).ranges() == vec![IntRange::From(21)]);
+    }
+    #[test]
+    fn symmetric_difference_keeps_unshared_values() {
+        let x: RangeSet<u32> = IntRange::Bound(10, 20).into();
+        let y: RangeSet<u32> = IntRange::Bound(15, 30).into();
+        let expected: RangeSet<u32> =
+            vec![IntRange::Bound(10, 14), IntRange::Bound(21, 30)]
+            .into_iter().collect();
+        assert_eq!(x.symmetric_difference(&y), expected);
+        assert_eq!(x ^ y, expected);
+    }
+    #[test]
+    fn subset_superset_disjoint() {
+        let x: RangeSet<i64> = IntRange::Bound(10, 20).into();
+        let y: RangeSet<i64> = IntRange::Bound(12, 15).into();
+        let z: RangeSet<i64> = IntRange::From(21).into();
+        assert!(y.is_subset(&x));
+        assert!(!x.is_subset(&y));
+        assert!(x.is_superset(&y));
+        assert!(x.is_disjoint(&z));
+        assert!(!x.is_disjoint(&y));
+        assert!(RangeSet::new().is_subset(&x));
+    }
+    #[test]
+    fn gaps_match_complement() {
+        let range_sets: Vec<RangeSet<i8>> = vec![
+            RangeSet::new(),
+            RangeSet::full(),
+            IntRange::To(0).into(),
+            IntRange::From(0).into(),
+            vec![IntRange::Bound(-5, 5), IntRange::Bound(10, 20)]
+                .into_iter().collect(),
+            vec![IntRange::To(-100), IntRange::Bound(0, 0),
+                 IntRange::From(100)].into_iter().collect(),
+            ];
+        for range_set in range_sets.iter() {
+            assert_eq!(range_set.gaps().collect::<Vec<_>>(),
+                       range_set.complement().ranges());
+        }
+    }
+    #[test]
+    fn complement_in_domain() {
+        let domain: RangeSet<u8> = IntRange::Bound(10, 20).into();
+        let x: RangeSet<u8> =
+            vec![IntRange::To(12), IntRange::From(18)].into_iter().collect();
+        assert_eq!(x.complement_in(&domain).ranges(),
+                   vec![IntRange::Bound(13, 17)]);
+        assert_eq!(x.complement_in(&RangeSet::full()), x.complement());
+    }
+    #[test]
+    fn not_is_complement() {
+        let x: RangeSet<i64> = IntRange::Bound(10, 20).into();
+        assert_eq!(!&x, x.complement());
+        assert_eq!(!!x.clone(), x);
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct MergeRange<T: Int> {
     start: T,
     end: T,
@@ -433,37 +1159,33 @@ enum MergeResult<T: Int> {
 impl<T: Int> MergeRange<T> {
     fn from_range(start: T, end: T) -> Self {
         debug_assert!(start <= end);
-        MergeRange{start: start, end: end}
+        MergeRange{start, end}
     }
     #[cfg(test)]
     fn to_range(self) -> (T, T) {
         (self.start, self.end)
     }
     fn from_range_to(end: T) -> Self {
-        MergeRange::from_range(<T as Int>::min_value(), end)
+        MergeRange::from_range(T::min_value(), end)
     }
     fn from_range_from(start: T) -> Self {
-        MergeRange::from_range(start, <T as Int>::max_value())
+        MergeRange::from_range(start, T::max_value())
     }
     fn range_full() -> Self {
-        MergeRange::from_range(<T as Int>::min_value(), <T as Int>::max_value())
+        MergeRange::from_range(T::min_value(), T::max_value())
     }
     fn concatenate(self, other: Self) -> Option<Self> {
-        if self.end < (<T as Int>::max_value()) &&
-            self.end + <T as Int>::one() == other.start {
-                Some(MergeRange::from_range(self.start, other.end))
-            } else {
-                None
-            }
+        match self.end.successor() {
+            Some(next) if next == other.start =>
+                Some(MergeRange::from_range(self.start, other.end)),
+            _ => None,
+        }
     }
     fn merge(self, other: Self) -> MergeResult<T> {
         // Check for adjacent ranges that can be concatenated.
-        match self.concatenate(other) {
-            Some(concat) => return Adjacent(concat),
-            _ => match other.concatenate(self) {
-                Some(concat) => return Adjacent(concat),
-                _ => (),
-            }
+        if let Some(concat) = self.concatenate(other)
+            .or_else(|| other.concatenate(self)) {
+            return Adjacent(concat);
         }
         // Check for overlap in the ranges.
         if self.start <= other.end && other.start <= self.end {
@@ -479,7 +1201,7 @@ impl<T: Int> MergeRange<T> {
 
 #[cfg(test)]
 mod merge_range_tests {
-    use std::num::Int;
+    use super::Int;
     use super::MergeRange;
     use super::MergeResult::*;
     #[test]
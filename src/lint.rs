@@ -0,0 +1,573 @@
+//! A linter for the integer `match` expressions in Rust source code.
+//!
+//! Requires the `lint` feature. The source is parsed with `syn`, and every
+//! `match` whose patterns are integer or `char` values and ranges is checked
+//! with `MatchAnalysis`. The scrutinee type is taken from a cast or a suffixed
+//! literal in the scrutinee, from the annotated type of the variable it names,
+//! or else from the patterns themselves. Matches whose type cannot be found,
+//! or whose patterns refer to other constants, are skipped.
+
+use std::fmt::{self, Display, Formatter};
+
+use proc_macro2::Span;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::{Arm, Expr, ExprLit, ExprMatch, ExprUnary, Lit, Pat, Path};
+use syn::{RangeLimits, Type, UnOp};
+
+use super::{Guard, Int, IntRange, MatchAnalysis, Notation, RangeList};
+use super::{RangeSet, RangeStyle, Reachability};
+
+// The names of the types that can be checked.
+const TYPE_NAMES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize",
+    "i8", "i16", "i32", "i64", "i128", "isize", "char",
+];
+
+/// A problem found in a `match` expression.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Finding {
+    /// The line of the `match` keyword or arm, counting from 1.
+    pub line: usize,
+    /// The column of the `match` keyword or arm, counting from 1.
+    pub column: usize,
+    /// The name of the scrutinee type.
+    pub ty: &'static str,
+    pub kind: FindingKind,
+}
+
+/// The kinds of problem found by the linter. Lists of values are written in
+/// pattern syntax, e.g. `10..=20 | 30`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum FindingKind {
+    /// The arms leave these values unmatched, or only matched by guarded
+    /// arms.
+    Uncovered(String),
+    /// The arm overlaps earlier arms on these values, which never reach it.
+    Overlap(String),
+    /// The arm can never be reached.
+    Unreachable,
+    /// These values are only matched by a wildcard or binding arm, such as
+    /// `_`, so a case may have been forgotten.
+    WildcardOnly(String),
+}
+
+impl Display for Finding {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        match self.kind {
+            FindingKind::Uncovered(ref values) =>
+                write!(formatter,
+                       "`match` on `{}` is not exhaustive; uncovered: {}",
+                       self.ty, values),
+            FindingKind::Overlap(ref values) =>
+                write!(formatter, "arm overlaps earlier arms on {}", values),
+            FindingKind::Unreachable =>
+                formatter.write_str("arm is unreachable"),
+            FindingKind::WildcardOnly(ref values) =>
+                write!(formatter, "only the wildcard arm matches {}", values),
+        }
+    }
+}
+
+/// Lints every `match` expression in a Rust source file. Returns the
+/// findings in source order, or an error if the source does not parse.
+pub fn lint_source(source: &str) -> syn::Result<Vec<Finding>> {
+    let file = syn::parse_file(source)?;
+    let mut linter = Linter{bindings: Vec::new(), findings: Vec::new()};
+    linter.visit_file(&file);
+    linter.findings.sort_by_key(|finding| (finding.line, finding.column));
+    Ok(linter.findings)
+}
+
+struct Linter {
+    // The variables in scope, innermost last, with their types if annotated
+    // with one that can be checked.
+    bindings: Vec<(String, Option<&'static str>)>,
+    findings: Vec<Finding>,
+}
+
+impl Linter {
+    // Find the type of a scrutinee from the expression itself.
+    fn expr_type(&self, expr: &Expr) -> Option<&'static str> {
+        match *expr {
+            Expr::Cast(ref cast) => type_name(&cast.ty),
+            Expr::Path(ref path) => {
+                let ident = path.path.get_ident()?.to_string();
+                self.bindings.iter().rev()
+                    .find(|binding| binding.0 == ident)?.1
+            },
+            Expr::Lit(ExprLit{ref lit, ..}) => lit_type(lit),
+            Expr::Paren(ref paren) => self.expr_type(&paren.expr),
+            Expr::Group(ref group) => self.expr_type(&group.expr),
+            Expr::Reference(ref reference) => self.expr_type(&reference.expr),
+            Expr::Unary(ExprUnary{op: UnOp::Deref(_), ref expr, ..}) =>
+                self.expr_type(expr),
+            _ => None,
+        }
+    }
+    fn lint_match(&mut self, node: &ExprMatch) {
+        let ty = match self.expr_type(&node.expr) {
+            Some(ty) => ty,
+            None => match node.arms.iter().find_map(|arm| pat_type(&arm.pat)) {
+                Some(ty) => ty,
+                None => return,
+            },
+        };
+        let findings = &mut self.findings;
+        macro_rules! dispatch {
+            ($($t:ident),*) => {
+                match ty {
+                    $(stringify!($t) => lint_arms::<$t>(node, findings),)*
+                    _ => unreachable!(),
+                }
+            }
+        }
+        dispatch!(u8, u16, u32, u64, u128, usize,
+                  i8, i16, i32, i64, i128, isize, char)
+    }
+    // Visit a node in a new scope, dropping its bindings afterward.
+    fn scoped<F: FnOnce(&mut Self)>(&mut self, visit: F) {
+        let len = self.bindings.len();
+        visit(self);
+        self.bindings.truncate(len);
+    }
+}
+
+impl<'ast> Visit<'ast> for Linter {
+    fn visit_block(&mut self, node: &'ast syn::Block) {
+        self.scoped(|linter| visit::visit_block(linter, node));
+    }
+    fn visit_item_fn(&mut self, node: &'ast syn::ItemFn) {
+        self.scoped(|linter| visit::visit_item_fn(linter, node));
+    }
+    fn visit_impl_item_fn(&mut self, node: &'ast syn::ImplItemFn) {
+        self.scoped(|linter| visit::visit_impl_item_fn(linter, node));
+    }
+    fn visit_trait_item_fn(&mut self, node: &'ast syn::TraitItemFn) {
+        self.scoped(|linter| visit::visit_trait_item_fn(linter, node));
+    }
+    fn visit_expr_closure(&mut self, node: &'ast syn::ExprClosure) {
+        self.scoped(|linter| visit::visit_expr_closure(linter, node));
+    }
+    fn visit_arm(&mut self, node: &'ast Arm) {
+        self.scoped(|linter| visit::visit_arm(linter, node));
+    }
+    fn visit_expr_for_loop(&mut self, node: &'ast syn::ExprForLoop) {
+        // The iterator cannot see the loop variables.
+        self.visit_expr(&node.expr);
+        self.scoped(|linter| {
+            linter.visit_pat(&node.pat);
+            linter.visit_block(&node.body);
+        });
+    }
+    fn visit_expr_if(&mut self, node: &'ast syn::ExprIf) {
+        // Bindings made by `if let` are seen by the first branch only.
+        self.scoped(|linter| {
+            linter.visit_expr(&node.cond);
+            linter.visit_block(&node.then_branch);
+        });
+        if let Some((_, ref else_branch)) = node.else_branch {
+            self.visit_expr(else_branch);
+        }
+    }
+    fn visit_expr_while(&mut self, node: &'ast syn::ExprWhile) {
+        self.scoped(|linter| visit::visit_expr_while(linter, node));
+    }
+    fn visit_expr_let(&mut self, node: &'ast syn::ExprLet) {
+        // The scrutinee cannot see the new bindings.
+        self.visit_expr(&node.expr);
+        self.visit_pat(&node.pat);
+    }
+    fn visit_local(&mut self, node: &'ast syn::Local) {
+        // The initializer, and any `else` block, cannot see the new
+        // bindings.
+        if let Some(ref init) = node.init {
+            self.visit_local_init(init);
+        }
+        self.visit_pat(&node.pat);
+    }
+    fn visit_pat_type(&mut self, node: &'ast syn::PatType) {
+        self.visit_type(&node.ty);
+        match *node.pat {
+            Pat::Ident(ref pat_ident) => {
+                self.bindings.push((pat_ident.ident.to_string(),
+                                    type_name(&node.ty)));
+                if let Some((_, ref subpat)) = pat_ident.subpat {
+                    self.visit_pat(subpat);
+                }
+            },
+            ref pat => self.visit_pat(pat),
+        }
+    }
+    fn visit_pat_ident(&mut self, node: &'ast syn::PatIdent) {
+        // A binding without a type shadows any earlier one.
+        self.bindings.push((node.ident.to_string(), None));
+        visit::visit_pat_ident(self, node);
+    }
+    fn visit_expr_match(&mut self, node: &'ast ExprMatch) {
+        self.lint_match(node);
+        visit::visit_expr_match(self, node);
+    }
+}
+
+// Analyze the arms of a match on `T`, adding the findings.
+fn lint_arms<T: Int>(node: &ExprMatch, findings: &mut Vec<Finding>) {
+    let mut patterns = Vec::new();
+    for arm in node.arms.iter() {
+        match arm_pattern::<T>(arm) {
+            Some(pattern) => patterns.push(pattern),
+            None => return,
+        }
+    }
+    let analysis = MatchAnalysis::from_guarded_patterns(
+        patterns.iter().map(|&(ref set, guard, _)| (set, guard)));
+    let style = RangeStyle::new(Notation::Pattern);
+    let values = |set: &RangeSet<T>| {
+        RangeList::new(&set.ranges()).style(style).to_string()
+    };
+    let mut push = |span: Span, kind| {
+        let start = span.start();
+        findings.push(Finding{line: start.line, column: start.column + 1,
+                              ty: T::NAME, kind});
+    };
+    let uncovered = !analysis.covered();
+    if !uncovered.is_empty() {
+        push(node.match_token.span, FindingKind::Uncovered(values(&uncovered)));
+    }
+    for ((report, arm), &(_, _, wildcard)) in analysis.arms.iter()
+        .zip(node.arms.iter()).zip(patterns.iter()) {
+        let span = arm.pat.span();
+        match report.reachability {
+            Reachability::Unreachable =>
+                push(span, FindingKind::Unreachable),
+            // Overlap is the point of a wildcard.
+            Reachability::PartiallyReachable if !wildcard =>
+                push(span, FindingKind::Overlap(values(&report.shadowed))),
+            _ => if wildcard && report.guard == Guard::Unguarded {
+                push(span, FindingKind::WildcardOnly(
+                    values(&report.effective)));
+            },
+        }
+    }
+}
+
+// Read the pattern and guard of an arm, and whether it is a catch-all.
+fn arm_pattern<T: Int>(arm: &Arm) -> Option<(RangeSet<T>, Guard, bool)> {
+    let guard = match arm.guard {
+        Some(_) => Guard::Guarded,
+        None => Guard::Unguarded,
+    };
+    let wildcard = is_wildcard(&arm.pat);
+    Some((pattern_set(&arm.pat)?, guard, wildcard))
+}
+
+fn is_wildcard(pat: &Pat) -> bool {
+    match *pat {
+        Pat::Wild(_) => true,
+        Pat::Ident(ref pat_ident) => match pat_ident.subpat {
+            Some((_, ref subpat)) => is_wildcard(subpat),
+            None => true,
+        },
+        Pat::Paren(ref paren) => is_wildcard(&paren.pat),
+        _ => false,
+    }
+}
+
+// Read a pattern as the set of values it matches, or return `None` if it
+// is not an integer pattern.
+fn pattern_set<T: Int>(pat: &Pat) -> Option<RangeSet<T>> {
+    match *pat {
+        Pat::Wild(_) => Some(RangeSet::full()),
+        Pat::Ident(ref pat_ident) => match pat_ident.subpat {
+            Some((_, ref subpat)) => pattern_set(subpat),
+            // By convention, a capitalized name is a constant, whose value
+            // is unknown.
+            None => if pat_ident.ident.to_string()
+                .starts_with(|c: char| c.is_uppercase()) {
+                None
+            } else {
+                Some(RangeSet::full())
+            },
+        },
+        Pat::Lit(ref lit) => {
+            let x = value(&Expr::Lit(lit.clone()))?;
+            Some(IntRange::Bound(x, x).into())
+        },
+        Pat::Path(ref path) if path.qself.is_none() => {
+            let x = constant(&path.path)?;
+            Some(IntRange::Bound(x, x).into())
+        },
+        Pat::Range(ref range) => {
+            let start = match range.start {
+                Some(ref start) => Some(value(start)?),
+                None => None,
+            };
+            let end = match (&range.end, range.limits) {
+                (Some(end), RangeLimits::Closed(_)) => Some(value(end)?),
+                // An exclusive end at the minimum leaves the range empty.
+                (Some(end), RangeLimits::HalfOpen(_)) =>
+                    match value::<T>(end)?.predecessor() {
+                        Some(end) => Some(end),
+                        None => return Some(RangeSet::new()),
+                    },
+                (None, _) => None,
+            };
+            Some(match (start, end) {
+                (Some(start), Some(end)) if start > end => RangeSet::new(),
+                (Some(start), Some(end)) => IntRange::Bound(start, end).into(),
+                (None, Some(end)) => IntRange::To(end).into(),
+                (Some(start), None) => IntRange::From(start).into(),
+                (None, None) => RangeSet::full(),
+            })
+        },
+        Pat::Or(ref or) => {
+            let mut set = RangeSet::new();
+            for case in or.cases.iter() {
+                set = set | pattern_set(case)?;
+            }
+            Some(set)
+        },
+        Pat::Paren(ref paren) => pattern_set(&paren.pat),
+        Pat::Reference(ref reference) => pattern_set(&reference.pat),
+        _ => None,
+    }
+}
+
+// Read a literal or constant expression as a value of `T`.
+fn value<T: Int>(expr: &Expr) -> Option<T> {
+    match *expr {
+        Expr::Lit(ExprLit{lit: Lit::Int(ref lit), ..}) => {
+            if !lit.suffix().is_empty() && lit.suffix() != T::NAME {
+                return None;
+            }
+            // Negative literals in patterns are read as one token.
+            let digits = lit.base10_digits();
+            let (negative, magnitude) = match digits.strip_prefix('-') {
+                Some(magnitude) => (true, magnitude),
+                None => (false, digits),
+            };
+            T::from_sign_magnitude(negative, magnitude.parse().ok()?)
+        },
+        Expr::Lit(ExprLit{lit: Lit::Char(ref lit), ..}) if T::NAME == "char" =>
+            T::from_sign_magnitude(false, lit.value() as u128),
+        Expr::Lit(ExprLit{lit: Lit::Byte(ref lit), ..}) if T::NAME == "u8" =>
+            T::from_sign_magnitude(false, lit.value() as u128),
+        Expr::Unary(ExprUnary{op: UnOp::Neg(_), ref expr, ..}) => {
+            let (negative, magnitude) = value::<T>(expr)?.to_sign_magnitude();
+            T::from_sign_magnitude(!negative, magnitude)
+        },
+        Expr::Paren(ref paren) => value(&paren.expr),
+        Expr::Group(ref group) => value(&group.expr),
+        Expr::Path(ref path) if path.qself.is_none() => constant(&path.path),
+        _ => None,
+    }
+}
+
+// Read `T::MIN` or `T::MAX`, possibly by way of `std` or `core`.
+fn constant<T: Int>(path: &Path) -> Option<T> {
+    let segments: Vec<String> = path.segments.iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let (name, ty) = match segments.iter().map(|x| x.as_str())
+        .collect::<Vec<_>>()[..] {
+        [ty, name] | ["std", ty, name] | ["core", ty, name] => (name, ty),
+        _ => return None,
+    };
+    match name {
+        "MIN" if ty == T::NAME => Some(T::min_value()),
+        "MAX" if ty == T::NAME => Some(T::max_value()),
+        _ => None,
+    }
+}
+
+// Find the checked type named by a type, looking through references.
+fn type_name(ty: &Type) -> Option<&'static str> {
+    match *ty {
+        Type::Path(ref path) if path.qself.is_none() => {
+            let ident = path.path.get_ident()?;
+            TYPE_NAMES.iter().find(|&&name| ident == name).cloned()
+        },
+        Type::Reference(ref reference) => type_name(&reference.elem),
+        Type::Paren(ref paren) => type_name(&paren.elem),
+        Type::Group(ref group) => type_name(&group.elem),
+        _ => None,
+    }
+}
+
+// Find the type of a literal, if it has a suffix or is a character.
+fn lit_type(lit: &Lit) -> Option<&'static str> {
+    match *lit {
+        Lit::Int(ref lit) =>
+            TYPE_NAMES.iter().find(|&&name| lit.suffix() == name).cloned(),
+        Lit::Char(_) => Some("char"),
+        Lit::Byte(_) => Some("u8"),
+        _ => None,
+    }
+}
+
+// Find the type of a pattern from its literals and constants.
+fn pat_type(pat: &Pat) -> Option<&'static str> {
+    let expr_type = |expr: &Expr| match *expr {
+        Expr::Lit(ExprLit{ref lit, ..}) => lit_type(lit),
+        Expr::Path(ref path) => path_type(&path.path),
+        _ => None,
+    };
+    match *pat {
+        Pat::Lit(ref lit) => lit_type(&lit.lit),
+        Pat::Path(ref path) => path_type(&path.path),
+        Pat::Range(ref range) => range.start.iter().chain(range.end.iter())
+            .find_map(|expr| expr_type(expr)),
+        Pat::Ident(ref pat_ident) =>
+            pat_type(&pat_ident.subpat.as_ref()?.1),
+        Pat::Or(ref or) => or.cases.iter().find_map(pat_type),
+        Pat::Paren(ref paren) => pat_type(&paren.pat),
+        Pat::Reference(ref reference) => pat_type(&reference.pat),
+        _ => None,
+    }
+}
+
+// Find the type of a constant like `u8::MAX`.
+fn path_type(path: &Path) -> Option<&'static str> {
+    let len = path.segments.len();
+    let ty = &path.segments.iter().nth(len.checked_sub(2)?)?.ident;
+    TYPE_NAMES.iter().find(|&&name| ty == name).cloned()
+}
+
+#[cfg(test)]
+mod lint_tests {
+    use super::{Finding, FindingKind, lint_source};
+    // Lint the source, returning the findings as `line:column: message`.
+    fn lint(source: &str) -> Vec<String> {
+        lint_source(source).unwrap().iter()
+            .map(|finding| format!("{}:{}: {}", finding.line, finding.column,
+                                   finding))
+            .collect()
+    }
+    #[test]
+    fn annotated_parameter() {
+        let source = "
+fn f(x: u8) -> u8 {
+    match x {
+        0..=9 => 0,
+        5..=20 => 1,
+        30 | 40 => 2,
+    }
+}";
+        assert_eq!(lint(source), vec![
+            "3:5: `match` on `u8` is not exhaustive; uncovered: \
+             21..=29 | 31..=39 | 41..=u8::MAX",
+            "5:9: arm overlaps earlier arms on 5..=9",
+            ]);
+    }
+    #[test]
+    fn type_from_patterns_and_casts() {
+        let source = "
+fn f(y: Foo) {
+    match y.0 as i8 {
+        i8::MIN..=-1 => {},
+        0 => {},
+        -3..=-2 => {},
+        _ => {},
+    }
+    match c {
+        'a'..='z' | 'A'..='Z' => {},
+        other => {},
+    }
+    let z = 5u16;
+    match z {
+        ..=9 => {},
+        std::u16::MAX => {},
+    }
+}";
+        assert_eq!(lint(source), vec![
+            "6:9: arm is unreachable",
+            "7:9: only the wildcard arm matches 1..=i8::MAX",
+            "11:9: only the wildcard arm matches \
+             char::MIN..='@' | '['..='`' | '{'..=char::MAX",
+            "14:5: `match` on `u16` is not exhaustive; uncovered: 10..=65534",
+            ]);
+    }
+    #[test]
+    fn guards_and_bindings() {
+        let source = "
+fn f(x: u8) {
+    let y: i32 = 0;
+    match y {
+        n @ 0..=9 if n % 2 == 0 => {},
+        10.. => {},
+        ..=-1 => {},
+    }
+    {
+        let y: u8 = 0;
+    }
+    match y {
+        0..=9 => {},
+    }
+}";
+        assert_eq!(lint_source(source).unwrap(), vec![
+            Finding{line: 4, column: 5, ty: "i32",
+                    kind: FindingKind::Uncovered("0..=9".to_string())},
+            Finding{line: 12, column: 5, ty: "i32",
+                    kind: FindingKind::Uncovered(
+                        "i32::MIN..=-1 | 10..=i32::MAX".to_string())},
+            ]);
+    }
+    #[test]
+    fn pattern_bindings_shadow() {
+        let source = "
+fn f(x: u8, v: Vec<i32>) {
+    for x in v.iter() {
+        match *x {
+            0..=255 => {},
+            _ => {},
+        }
+    }
+    let g = |x| match x { 0..=9 => 1, _ => 2 };
+    if let Some(x) = v.first() {
+        match x { 0..=255 => {}, _ => {} }
+    }
+    while let (x, _) = (v[0], 0) {
+        match x { 0..=255 => {}, _ => {} }
+    }
+    match v.len() {
+        x => match x { 0..=255 => {}, _ => {} },
+    }
+    let (x, _) = (v[0], 1);
+    match x { 0..=255 => {}, _ => {} }
+}";
+        assert!(lint(source).is_empty());
+    }
+    #[test]
+    fn shadowing_ends_with_scope() {
+        let source = "
+fn f(x: u8, v: Vec<i32>) {
+    for x in v.iter() {}
+    if let Some(x) = v.first() {
+    } else {
+        match x { 0..=255 => {}, _ => {} }
+    }
+    let x = match x { 0..=9 => x, 10.. => 0 };
+}";
+        assert_eq!(lint(source), vec![
+            "6:34: arm is unreachable",
+            ]);
+    }
+    #[test]
+    fn unknown_matches_skipped() {
+        let source = "
+fn f(x: u8, e: Enum) {
+    match x {
+        LIMIT => {},
+        _ => {},
+    }
+    match e {
+        Enum::A => {},
+    }
+    match x {
+        0i8 => {},
+    }
+}";
+        assert!(lint(source).is_empty());
+        assert!(lint_source("fn f( {").is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! Maps from disjoint integer ranges to values.
+
+use std::cmp::{max, min};
+use std::ops::RangeBounds;
+
+use super::{Int, IntRange, MergeRange, RangeSet};
+use super::convert::merge_range_from_bounds;
+use super::MergeResult::*;
+
+/// A map from disjoint ranges of integers to values, such as a lookup table
+/// built from ranges that have passed `uncovered_and_overlapped`.
+///
+/// Entries are kept in ascending order. Inserting a range that overlaps
+/// existing entries splits them, and the new value replaces theirs on the
+/// overlap. Adjacent entries may hold equal values until `coalesce` is
+/// called.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RangeMap<T: Int, V> {
+    entries: Vec<(MergeRange<T>, V)>,
+}
+
+impl<T: Int, V> RangeMap<T, V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        RangeMap{entries: Vec::new()}
+    }
+    /// Returns true if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns the number of entries, each a range with its value.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Returns the value for `key`, if any.
+    pub fn get(&self, key: T) -> Option<&V> {
+        self.get_entry(key).map(|(_, value)| value)
+    }
+    /// Returns the entry containing `key`, if any, as its range and value.
+    pub fn get_entry(&self, key: T) -> Option<(IntRange<T>, &V)> {
+        // The entries are disjoint and in order, so only the last one
+        // starting at or below the key can contain it.
+        let i = self.entries.partition_point(|entry| entry.0.start <= key);
+        let (range, ref value) = *self.entries.get(i.checked_sub(1)?)?;
+        if key <= range.end {
+            Some((IntRange::from_merge_range(range), value))
+        } else {
+            None
+        }
+    }
+    /// Iterates over the entries in ascending order.
+    pub fn iter(&self) -> Entries<'_, T, V> {
+        Entries{inner: self.entries.iter()}
+    }
+    /// Returns the set of keys that have a value.
+    pub fn domain(&self) -> RangeSet<T> {
+        let mut range_set = RangeSet::new();
+        for &(range, _) in self.entries.iter() {
+            range_set.push_last(range);
+        }
+        range_set
+    }
+    /// Returns the ranges of keys that have no value, in the same form as
+    /// the first list returned by `uncovered_and_overlapped`.
+    pub fn uncovered(&self) -> Vec<IntRange<T>> {
+        self.domain().gaps().collect()
+    }
+    /// Merges adjacent entries with equal values, so that the map has as
+    /// few entries as possible.
+    pub fn coalesce(&mut self) where V: PartialEq {
+        let mut entries: Vec<(MergeRange<T>, V)> =
+            Vec::with_capacity(self.entries.len());
+        for (range, value) in self.entries.drain(..) {
+            if let Some(last) = entries.last_mut() {
+                if last.1 == value {
+                    if let Adjacent(concat) = last.0.merge(range) {
+                        last.0 = concat;
+                        continue;
+                    }
+                }
+            }
+            entries.push((range, value));
+        }
+        self.entries = entries;
+    }
+}
+
+impl<T: Int, V: Clone> RangeMap<T, V> {
+    /// Builds a map from a list of entries, which may take any of the range
+    /// types accepted by `uncovered_and_overlapped`. Where entries collide,
+    /// the later one wins.
+    ///
+    /// Also returns the collisions: the ranges of keys given by more than one
+    /// entry, in the same form as the second list returned by
+    /// `uncovered_and_overlapped`.
+    pub fn from_entries<R, I>(entries: I) -> (Self, Vec<IntRange<T>>)
+        where R: RangeBounds<T>, I: IntoIterator<Item=(R, V)> {
+        let mut map = RangeMap::new();
+        let mut collisions = RangeSet::new();
+        for (range, value) in entries {
+            for (displaced, _) in map.insert(range, value) {
+                collisions.insert(displaced);
+            }
+        }
+        (map, collisions.ranges())
+    }
+    /// Maps every key in `range` to `value`. Existing entries that overlap
+    /// the range are split, and their values on the overlap are returned in
+    /// ascending order. An empty range is ignored.
+    pub fn insert<R>(&mut self, range: R, value: V) -> Vec<(IntRange<T>, V)>
+        where R: RangeBounds<T> {
+        let new_range = match merge_range_from_bounds(&range) {
+            Some(new_range) => new_range,
+            None => return Vec::new(),
+        };
+        // The entries that overlap the new range are contiguous.
+        let first = self.entries
+            .partition_point(|entry| entry.0.end < new_range.start);
+        let last = self.entries
+            .partition_point(|entry| entry.0.start <= new_range.end);
+        let overlapped: Vec<_> = self.entries.drain(first..last).collect();
+        let mut replacement = Vec::with_capacity(3);
+        let mut displaced = Vec::with_capacity(overlapped.len());
+        let mut right_piece = None;
+        for (range, old_value) in overlapped {
+            // Keep the parts of the entry that stick out on either side.
+            if range.start < new_range.start {
+                let end = new_range.start.predecessor().unwrap();
+                replacement.push((MergeRange::from_range(range.start, end),
+                                  old_value.clone()));
+            }
+            if range.end > new_range.end {
+                let start = new_range.end.successor().unwrap();
+                right_piece = Some((MergeRange::from_range(start, range.end),
+                                    old_value.clone()));
+            }
+            let overlap = MergeRange::from_range(
+                max(range.start, new_range.start),
+                min(range.end, new_range.end));
+            displaced.push((IntRange::from_merge_range(overlap), old_value));
+        }
+        replacement.push((new_range, value));
+        replacement.extend(right_piece);
+        self.entries.splice(first..first, replacement);
+        displaced
+    }
+}
+
+impl<T: Int, V> Default for RangeMap<T, V> {
+    fn default() -> Self {
+        RangeMap::new()
+    }
+}
+
+impl<T: Int, V: Clone> FromIterator<(IntRange<T>, V)> for RangeMap<T, V> {
+    fn from_iter<I: IntoIterator<Item=(IntRange<T>, V)>>(iter: I) -> Self {
+        RangeMap::from_entries(iter).0
+    }
+}
+
+impl<T: Int, V: Clone> Extend<(IntRange<T>, V)> for RangeMap<T, V> {
+    fn extend<I: IntoIterator<Item=(IntRange<T>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<'a, T: Int, V> IntoIterator for &'a RangeMap<T, V> {
+    type Item = (IntRange<T>, &'a V);
+    type IntoIter = Entries<'a, T, V>;
+    fn into_iter(self) -> Entries<'a, T, V> {
+        self.iter()
+    }
+}
+
+/// Iterator over the entries of a `RangeMap`.
+#[derive(Clone, Debug)]
+pub struct Entries<'a, T: Int + 'a, V: 'a> {
+    inner: std::slice::Iter<'a, (MergeRange<T>, V)>,
+}
+
+impl<'a, T: Int, V> Iterator for Entries<'a, T, V> {
+    type Item = (IntRange<T>, &'a V);
+    fn next(&mut self) -> Option<(IntRange<T>, &'a V)> {
+        self.inner.next()
+            .map(|(range, value)| (IntRange::from_merge_range(*range), value))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<T: Int, V> DoubleEndedIterator for Entries<'_, T, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+            .map(|(range, value)| (IntRange::from_merge_range(*range), value))
+    }
+}
+
+impl<T: Int, V> ExactSizeIterator for Entries<'_, T, V> {}
+
+#[cfg(test)]
+mod map_tests {
+    use super::RangeMap;
+    use super::super::{IntRange, uncovered_and_overlapped};
+    use super::super::IntRange::*;
+    #[test]
+    fn lookup_in_disjoint_table() {
+        let (map, collisions) = RangeMap::from_entries([
+            (0u8..=9, "digit"),
+            (b'a'..=b'z', "lower"),
+            (b'A'..=b'Z', "upper"),
+            ]);
+        assert!(collisions.is_empty());
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(5), Some(&"digit"));
+        assert_eq!(map.get(b'Q'), Some(&"upper"));
+        assert_eq!(map.get(10), None);
+        assert_eq!(map.get(255), None);
+        assert_eq!(map.get_entry(b'c'),
+                   Some((Bound(b'a', b'z'), &"lower")));
+        assert_eq!(map.iter().map(|(range, _)| range).collect::<Vec<_>>(),
+                   vec![To(9), Bound(b'A', b'Z'), Bound(b'a', b'z')]);
+    }
+    #[test]
+    fn insert_splits_overlapped_entries() {
+        let mut map = RangeMap::new();
+        map.insert(0i16..=99, 'a');
+        map.insert(200i16..=299, 'b');
+        let displaced = map.insert(50i16..250, 'c');
+        assert_eq!(displaced, vec![(Bound(50, 99), 'a'),
+                                   (Bound(200, 249), 'b')]);
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![
+            (Bound(0, 49), &'a'),
+            (Bound(50, 249), &'c'),
+            (Bound(250, 299), &'b'),
+            ]);
+        // Inserting inside one entry splits it in three.
+        map.insert(10i16..=19, 'd');
+        assert_eq!(map.iter().rev().map(|(_, &value)| value)
+                   .collect::<String>(), "bcada");
+        assert!(map.insert(5i16..5, 'e').is_empty());
+    }
+    #[test]
+    fn collisions_match_uncovered_and_overlapped() {
+        let entries = [
+            (Bound(6i8, 16), 1),
+            (To(-10i8), 2),
+            (From(15i8), 3),
+            (Bound(4i8, 7), 4),
+            ];
+        let (map, collisions) = RangeMap::from_entries(entries);
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped(entries.iter().map(|entry| entry.0));
+        assert_eq!(map.uncovered(), uncovered);
+        assert_eq!(collisions, overlapped);
+        // Later entries win.
+        assert_eq!(map.get(7), Some(&4));
+        assert_eq!(map.get(15), Some(&3));
+    }
+    #[test]
+    fn coalesce_merges_equal_neighbors() {
+        let mut map: RangeMap<u32, bool> = [
+            (Bound(0, 9), true),
+            (Bound(10, 19), true),
+            (Bound(21, 30), true),
+            (Bound(31, 40), false),
+            (From(41), false),
+            ].into_iter().collect();
+        map.coalesce();
+        assert_eq!(map.iter().collect::<Vec<_>>(), vec![
+            (To(19), &true),
+            (Bound(21, 30), &true),
+            (From(31), &false),
+            ]);
+        assert_eq!(map.domain().ranges(),
+                   vec![To(19u32), From(21)]);
+    }
+    #[test]
+    fn full_range_at_type_limits() {
+        let mut map = RangeMap::new();
+        map.insert(IntRange::Full, 0);
+        map.insert(IntRange::To(i64::MIN), 1);
+        map.insert(IntRange::From(i64::MAX), 2);
+        assert_eq!(map.get(i64::MIN), Some(&1));
+        assert_eq!(map.get(0), Some(&0));
+        assert_eq!(map.get(i64::MAX), Some(&2));
+        assert!(map.uncovered().is_empty());
+    }
+}
@@ -0,0 +1,536 @@
+//! Mask patterns: sets of values given by fixing some bits and leaving the
+//! rest free, as in the `(mask, value)` tables of instruction decoders.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+use super::{Int, IntRange, RangeSet};
+use super::convert::merge_range_from_bounds;
+
+/// The values whose bits under `mask` equal those of `value`, with the other
+/// bits free. Each bit of the pattern is 0, 1 or "don't care".
+///
+/// Bits are those of the two's complement form for signed types, and of the
+/// code point for `char`. Bit patterns that are not chars, such as the
+/// surrogates, are never matched, and are left out of the results of
+/// `uncovered_and_overlapped_masks`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MaskPattern<T: Int> {
+    mask: u128,
+    value: u128,
+    marker: PhantomData<T>,
+}
+
+impl<T: Int> MaskPattern<T> {
+    /// Creates the pattern matching values whose bits under `mask` equal
+    /// those of `value`. Bits of `value` outside `mask` are ignored.
+    ///
+    /// Panics if `mask` or `value` has bits above the width of `T`.
+    pub fn new(mask: u128, value: u128) -> Self {
+        assert!(mask & !width_mask::<T>() == 0 &&
+                value & !width_mask::<T>() == 0,
+                "mask pattern is wider than {}", T::NAME);
+        MaskPattern{mask, value: value & mask, marker: PhantomData}
+    }
+    /// Returns the pattern matching every value.
+    pub fn full() -> Self {
+        MaskPattern::new(0, 0)
+    }
+    /// Returns the bits that the pattern fixes.
+    pub fn mask(&self) -> u128 {
+        self.mask
+    }
+    /// Returns the fixed bits, with the free bits clear.
+    pub fn value(&self) -> u128 {
+        self.value
+    }
+    /// Returns true if the pattern matches `value`.
+    pub fn contains(&self, value: T) -> bool {
+        to_bits(value) & self.mask == self.value
+    }
+    /// Returns disjoint patterns matching exactly the values of `range`, in
+    /// ascending order of their bits, as for `masks_from_ranges`. An empty
+    /// range gives no patterns.
+    pub fn from_range(range: IntRange<T>) -> Vec<Self> {
+        masks_from_ranges([range])
+    }
+    /// Returns the disjoint ranges of values matched by the pattern, in
+    /// ascending order.
+    ///
+    /// Each run of values differs only in the free bits below the lowest
+    /// fixed bit, so a pattern with `k` free bits above its lowest fixed bit
+    /// makes up to `2^k` ranges. Fails with `MaskLimitError` if that is too
+    /// many, as for `masks_to_ranges`.
+    pub fn to_ranges(&self) -> Result<Vec<IntRange<T>>, MaskLimitError> {
+        masks_to_ranges([*self])
+    }
+    fn intersects(&self, other: &Self) -> bool {
+        (self.value ^ other.value) & self.mask & other.mask == 0
+    }
+    fn is_superset(&self, other: &Self) -> bool {
+        self.mask & !other.mask == 0 && other.value & self.mask == self.value
+    }
+}
+
+impl<T: Int> Display for MaskPattern<T> {
+    /// Writes the bits from the highest down, as `0`, `1` or `x` for a free
+    /// bit, in groups of four, as in `0001_xxxx`.
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        for i in (0..T::BITS).rev() {
+            let bit = 1u128 << i;
+            formatter.write_str(match (self.mask & bit, self.value & bit) {
+                (0, _) => "x",
+                (_, 0) => "0",
+                _ => "1",
+            })?;
+            if i > 0 && i % 4 == 0 {
+                formatter.write_str("_")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when mask patterns match too many separate runs of values
+/// to list them as ranges.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MaskLimitError;
+
+impl Display for MaskLimitError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "mask patterns match more than {} separate runs of \
+                           values", RUN_LIMIT)
+    }
+}
+
+impl Error for MaskLimitError {}
+
+// The most runs of values that `masks_to_ranges` goes through.
+const RUN_LIMIT: u128 = 1 << 20;
+
+/// The mask pattern version of `uncovered_and_overlapped`. Returns:
+///
+///  1) a list of patterns matching the values which no input pattern
+///     matches, and
+///
+///  2) a list of patterns matching the values which more than one input
+///     pattern matches.
+///
+/// Each list consists of disjoint patterns in ascending order of their bits,
+/// and depends only on the input patterns, not on their order. The bits are
+/// split one at a time, first those that most input patterns fix, and then
+/// patterns differing in a single fixed bit are merged until none are left.
+/// So no two patterns of a list can be merged into one, though a list is not
+/// always the fewest disjoint patterns possible, as finding those takes a
+/// search too costly for wide types.
+#[allow(clippy::type_complexity)]
+pub fn uncovered_and_overlapped_masks<T, I>(patterns: I)
+      -> (Vec<MaskPattern<T>>, Vec<MaskPattern<T>>)
+    where T: Int, I: IntoIterator, I::Item: Borrow<MaskPattern<T>> {
+    let patterns: Vec<MaskPattern<T>> =
+        patterns.into_iter().map(|pattern| *pattern.borrow()).collect();
+    let excluded = not_chars::<T>();
+    let mut uncovered = Vec::new();
+    let mut overlapped = Vec::new();
+    split(MaskPattern::full(), 0, &patterns, &excluded,
+          &mut uncovered, &mut overlapped);
+    (merge(uncovered), merge(overlapped))
+}
+
+/// Returns patterns matching exactly the values in any of the ranges, which
+/// are accepted in the same forms as for `uncovered_and_overlapped`. The
+/// patterns are disjoint and in ascending order of their bits.
+pub fn masks_from_ranges<T, I>(ranges: I) -> Vec<MaskPattern<T>>
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
+    let mut range_set = RangeSet::new();
+    for range in ranges {
+        if let Some(merge_range) = merge_range_from_bounds(&range) {
+            range_set.insert(IntRange::from_merge_range(merge_range));
+        }
+    }
+    let mut patterns = Vec::new();
+    for range in range_set.iter() {
+        for (start, end) in bit_ranges(range) {
+            push_blocks(start, end, &mut patterns);
+        }
+    }
+    merge(patterns)
+}
+
+/// Returns the disjoint ranges of values matched by any of the patterns, in
+/// ascending order.
+///
+/// A pattern leaving free bits above a fixed bit matches many separate runs
+/// of values, as `xxxx_xxx1` matches each odd value apart. Fails with
+/// `MaskLimitError` if the patterns make more than 2^20 runs in all, instead
+/// of going through them.
+pub fn masks_to_ranges<T, I>(patterns: I)
+      -> Result<Vec<IntRange<T>>, MaskLimitError>
+    where T: Int, I: IntoIterator, I::Item: Borrow<MaskPattern<T>> {
+    let mut ranges = Vec::new();
+    let mut budget = RUN_LIMIT;
+    for pattern in patterns {
+        let pattern = *pattern.borrow();
+        // The free bits below the lowest fixed bit make runs of values, and
+        // each choice of the free bits above it starts a new run.
+        let run = low_mask(pattern.mask.trailing_zeros()) & width_mask::<T>();
+        let free = !pattern.mask & width_mask::<T>() & !run;
+        budget = 1u128.checked_shl(free.count_ones())
+            .and_then(|runs| budget.checked_sub(runs))
+            .ok_or(MaskLimitError)?;
+        let mut choice = 0u128;
+        loop {
+            let start = pattern.value | choice;
+            ranges.extend(ranges_from_bits::<T>(start, start | run));
+            // Step to the next subset of the free bits.
+            choice = choice.wrapping_sub(free) & free;
+            if choice == 0 {
+                break;
+            }
+        }
+    }
+    // Sort and merge the runs all at once.
+    Ok(ranges.into_iter().collect::<RangeSet<T>>().ranges())
+}
+
+// Sort out the values matched by `cube`, which every pattern in `covering`
+// contains and every pattern in `patterns` intersects. Only whether one or
+// more patterns cover a value matters, so `covering` is capped at 2.
+fn split<T: Int>(cube: MaskPattern<T>, covering: usize,
+                 patterns: &[MaskPattern<T>], excluded: &[MaskPattern<T>],
+                 uncovered: &mut Vec<MaskPattern<T>>,
+                 overlapped: &mut Vec<MaskPattern<T>>) {
+    if excluded.iter().any(|pattern| pattern.is_superset(&cube)) {
+        return;
+    }
+    let (inside, partial): (Vec<_>, Vec<_>) =
+        patterns.iter().copied()
+            .partition(|pattern| pattern.is_superset(&cube));
+    let covering = (covering + inside.len()).min(2);
+    if covering == 2 && excluded.is_empty() {
+        overlapped.push(cube);
+        return;
+    }
+    // Split on the bit that the most patterns fix but the cube does not, the
+    // highest such bit on a tie, as this tends to give the fewest pieces. If
+    // there is none, every value in the cube is alike.
+    let fixed = partial.iter().chain(excluded.iter())
+        .fold(0, |fixed, pattern| fixed | pattern.mask) & !cube.mask;
+    if fixed == 0 {
+        match covering {
+            0 => uncovered.push(cube),
+            1 => (),
+            _ => overlapped.push(cube),
+        }
+        return;
+    }
+    let bit = (0..128).map(|i| 1u128 << i)
+        .filter(|bit| fixed & bit != 0)
+        .max_by_key(|bit| {
+            partial.iter().chain(excluded.iter())
+                .filter(|pattern| pattern.mask & bit != 0)
+                .count()
+        })
+        .unwrap();
+    for value in [cube.value, cube.value | bit] {
+        let half = MaskPattern::new(cube.mask | bit, value);
+        let patterns: Vec<_> = partial.iter().copied()
+            .filter(|pattern| pattern.intersects(&half))
+            .collect();
+        let excluded: Vec<_> = excluded.iter().copied()
+            .filter(|pattern| pattern.intersects(&half))
+            .collect();
+        split(half, covering, &patterns, &excluded, uncovered, overlapped);
+    }
+}
+
+// Merge disjoint patterns that differ in a single fixed bit, until no two
+// do, and sort the result. Patterns are taken in ascending order, and each is
+// merged on its lowest possible bit, so the result depends only on the input.
+fn merge<T: Int>(patterns: Vec<MaskPattern<T>>) -> Vec<MaskPattern<T>> {
+    let mut set: BTreeSet<(u128, u128)> = patterns.iter()
+        .map(|pattern| (pattern.mask, pattern.value))
+        .collect();
+    let mut pending: Vec<(u128, u128)> = set.iter().rev().copied().collect();
+    while let Some((mask, value)) = pending.pop() {
+        if !set.contains(&(mask, value)) {
+            continue;
+        }
+        let mut bits = mask;
+        while bits != 0 {
+            let bit = bits & bits.wrapping_neg();
+            bits &= !bit;
+            if set.remove(&(mask, value ^ bit)) {
+                set.remove(&(mask, value));
+                let merged = (mask & !bit, value & !bit);
+                set.insert(merged);
+                pending.push(merged);
+                break;
+            }
+        }
+    }
+    let mut merged: Vec<MaskPattern<T>> = set.into_iter()
+        .map(|(mask, value)| MaskPattern::new(mask, value))
+        .collect();
+    merged.sort_by_key(|pattern| (pattern.value, !pattern.mask));
+    merged
+}
+
+// Push the fewest patterns matching the bit patterns from `start` to `end`:
+// aligned blocks, each as large as fits.
+fn push_blocks<T: Int>(mut start: u128, end: u128,
+                       patterns: &mut Vec<MaskPattern<T>>) {
+    loop {
+        let mut size = start.trailing_zeros().min(T::BITS);
+        while low_mask(size) > end - start {
+            size -= 1;
+        }
+        let last = start + low_mask(size);
+        patterns.push(MaskPattern::new(width_mask::<T>() & !low_mask(size),
+                                       start));
+        if last == end {
+            break;
+        }
+        start = last + 1;
+    }
+}
+
+// The values that are not chars, for `char`, or none for other types.
+fn not_chars<T: Int>() -> Vec<MaskPattern<T>> {
+    let mut patterns = Vec::new();
+    if T::IS_CHAR {
+        push_blocks(0xD800, 0xDFFF, &mut patterns);
+        push_blocks(0x110000, width_mask::<T>(), &mut patterns);
+    }
+    patterns
+}
+
+// Split a range into runs of consecutive bit patterns.
+fn bit_ranges<T: Int>(range: IntRange<T>) -> Vec<(u128, u128)> {
+    let merge_range = match range.to_merge_range() {
+        Some(merge_range) => merge_range,
+        None => return Vec::new(),
+    };
+    let (start, end) = (to_bits(merge_range.start), to_bits(merge_range.end));
+    if T::IS_CHAR && start < 0xD800 && end > 0xDFFF {
+        // A char range across the surrogates.
+        vec![(start, 0xD7FF), (0xE000, end)]
+    } else if start <= end {
+        vec![(start, end)]
+    } else {
+        // A signed range from a negative value to a nonnegative one.
+        vec![(start, width_mask::<T>()), (0, end)]
+    }
+}
+
+// Turn a run of consecutive bit patterns into ranges of values, leaving out
+// those that are not values.
+fn ranges_from_bits<T: Int>(start: u128, end: u128) -> Vec<IntRange<T>> {
+    let mut runs = Vec::with_capacity(2);
+    if T::IS_CHAR {
+        runs.push((start, end.min(0xD7FF)));
+        runs.push((start.max(0xE000), end.min(0x10FFFF)));
+    } else if T::min_value().to_sign_magnitude().0 {
+        // The sign bit splits the nonnegative values from the negative ones.
+        let sign = 1u128 << (T::BITS - 1);
+        runs.push((start, end.min(sign - 1)));
+        runs.push((start.max(sign), end));
+    } else {
+        runs.push((start, end));
+    }
+    runs.into_iter()
+        .filter(|&(start, end)| start <= end)
+        .map(|(start, end)| IntRange::Bound(from_bits(start), from_bits(end)))
+        .collect()
+}
+
+// The bits below bit `bits`.
+fn low_mask(bits: u32) -> u128 {
+    if bits >= 128 { u128::MAX } else { (1 << bits) - 1 }
+}
+
+// The bits of the type.
+fn width_mask<T: Int>() -> u128 {
+    low_mask(T::BITS)
+}
+
+fn to_bits<T: Int>(value: T) -> u128 {
+    match value.to_sign_magnitude() {
+        (true, magnitude) => magnitude.wrapping_neg() & width_mask::<T>(),
+        (false, magnitude) => magnitude,
+    }
+}
+
+fn from_bits<T: Int>(bits: u128) -> T {
+    let negative = T::min_value().to_sign_magnitude().0 &&
+        bits >> (T::BITS - 1) != 0;
+    if negative {
+        let magnitude = bits.wrapping_neg() & width_mask::<T>();
+        T::from_sign_magnitude(true, magnitude).unwrap()
+    } else {
+        T::from_sign_magnitude(false, bits).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod mask_tests {
+    use super::{MaskLimitError, MaskPattern, masks_from_ranges,
+                masks_to_ranges};
+    use super::uncovered_and_overlapped_masks;
+    use super::super::{IntRange, uncovered_and_overlapped};
+    use super::super::IntRange::*;
+    use super::super::test_util::Lcg;
+    #[test]
+    fn decoder_table() {
+        let table = [
+            MaskPattern::<u16>::new(0xF000, 0x0000),
+            MaskPattern::new(0xF000, 0x1000),
+            MaskPattern::new(0xE000, 0x2000),
+            MaskPattern::new(0xC000, 0x4000),
+            MaskPattern::new(0x8001, 0x8000),
+            MaskPattern::new(0xF000, 0xC000),
+            ];
+        let (uncovered, overlapped) = uncovered_and_overlapped_masks(table);
+        let strings = |patterns: &[MaskPattern<u16>]| -> Vec<String> {
+            patterns.iter().map(|pattern| pattern.to_string()).collect()
+        };
+        assert_eq!(strings(&uncovered), vec![
+            "10xx_xxxx_xxxx_xxx1",
+            "1101_xxxx_xxxx_xxx1",
+            "111x_xxxx_xxxx_xxx1",
+            ]);
+        assert_eq!(strings(&overlapped), vec!["1100_xxxx_xxxx_xxx0"]);
+        assert!(uncovered.iter().all(|pattern| !pattern.contains(0x8000)));
+        assert!(overlapped[0].contains(0xC0FE));
+        // Fixing the table leaves every opcode decoding exactly once.
+        let mut fixed = table.to_vec();
+        fixed[4] = MaskPattern::new(0xC000, 0x8000);
+        fixed[5] = MaskPattern::new(0xC000, 0xC000);
+        assert_eq!(uncovered_and_overlapped_masks(&fixed), (vec![], vec![]));
+    }
+    #[test]
+    fn results_are_deterministic() {
+        let patterns = [
+            MaskPattern::<u8>::new(0xEE, 0xEE),
+            MaskPattern::new(0xBD, 0x21),
+            MaskPattern::new(0xE3, 0xE0),
+            MaskPattern::new(0x76, 0x04),
+            ];
+        let (uncovered, overlapped) = uncovered_and_overlapped_masks(patterns);
+        for _ in 0..10 {
+            assert_eq!(uncovered_and_overlapped_masks(patterns),
+                       (uncovered.clone(), overlapped.clone()));
+        }
+        assert_eq!(uncovered.iter().map(|pattern| pattern.to_string())
+                   .collect::<Vec<_>>(), vec![
+                       "x000_x0xx", "x000_x11x", "x001_xxxx", "0x10_00x0",
+                       "0x10_01xx", "0x10_1xxx", "0x11_xxxx", "x10x_xxxx",
+                       "101x_xxxx", "111x_xx01", "111x_0x1x", "111x_101x",
+                       ]);
+        assert!(overlapped.is_empty());
+    }
+    #[test]
+    fn ranges_to_masks_and_back() {
+        let patterns = MaskPattern::from_range(Bound(3u8, 12));
+        assert_eq!(patterns.iter().map(|pattern| pattern.to_string())
+                   .collect::<Vec<_>>(),
+                   vec!["0000_0011", "0000_01xx", "0000_10xx", "0000_1100"]);
+        assert_eq!(masks_to_ranges(&patterns), Ok(vec![Bound(3u8, 12)]));
+        assert_eq!(MaskPattern::<u8>::new(0x0C, 0x04).to_ranges(), Ok(vec![
+            Bound(4, 7), Bound(20, 23), Bound(36, 39), Bound(52, 55),
+            Bound(68, 71), Bound(84, 87), Bound(100, 103), Bound(116, 119),
+            Bound(132, 135), Bound(148, 151), Bound(164, 167),
+            Bound(180, 183), Bound(196, 199), Bound(212, 215),
+            Bound(228, 231), Bound(244, 247),
+            ]));
+        assert_eq!(MaskPattern::from_range(Full),
+                   vec![MaskPattern::<u64>::full()]);
+        assert!(MaskPattern::from_range(Bound(5u32, 4)).is_empty());
+        assert_eq!(masks_to_ranges([MaskPattern::<i128>::full()]),
+                   Ok(vec![IntRange::Full]));
+    }
+    #[test]
+    fn signed_values_use_twos_complement() {
+        // The sign bit alone matches the negative values.
+        let negative = MaskPattern::<i8>::new(0x80, 0x80);
+        assert!(negative.contains(-1));
+        assert!(!negative.contains(0));
+        assert_eq!(negative.to_ranges(), Ok(vec![To(-1)]));
+        assert_eq!(masks_from_ranges([-1i8..=0]),
+                   vec![MaskPattern::new(0xFF, 0x00),
+                        MaskPattern::new(0xFF, 0xFF)]);
+        assert_eq!(masks_from_ranges([To(-1i8), From(0)]),
+                   vec![MaskPattern::full()]);
+    }
+    #[test]
+    fn chars_leave_out_surrogates() {
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_masks([MaskPattern::<char>::new(0, 0)]);
+        assert!(uncovered.is_empty());
+        assert!(overlapped.is_empty());
+        // Characters up to U+FFFF leave the other planes uncovered.
+        let bmp = MaskPattern::<char>::new(0xFFFF0000, 0);
+        let (uncovered, _) = uncovered_and_overlapped_masks([bmp]);
+        assert_eq!(masks_to_ranges(&uncovered),
+                   Ok(vec![From('\u{10000}')]));
+        assert_eq!(bmp.to_ranges(), Ok(vec![To('\u{FFFF}')]));
+        assert_eq!(masks_to_ranges(masks_from_ranges([Bound('a', 'z'),
+                                                      From('π')])),
+                   Ok(vec![Bound('a', 'z'), From('π')]));
+    }
+    #[test]
+    fn matches_uncovered_and_overlapped() {
+        let mut lcg = Lcg::new(24680);
+        let mut next = || lcg.next_u16() as u128 & 0xFF;
+        for _ in 0..50 {
+            let patterns: Vec<MaskPattern<i8>> = (0..5)
+                .map(|_| MaskPattern::new(next() | next(), next()))
+                .collect();
+            let ranges: Vec<IntRange<i8>> = patterns.iter()
+                .flat_map(|pattern| pattern.to_ranges().unwrap())
+                .collect();
+            let (uncovered, overlapped) =
+                uncovered_and_overlapped_masks(&patterns);
+            let expected = uncovered_and_overlapped(&ranges);
+            assert_eq!(masks_to_ranges(&uncovered), Ok(expected.0));
+            for x in i8::MIN..=i8::MAX {
+                let count = patterns.iter()
+                    .filter(|pattern| pattern.contains(x))
+                    .count();
+                assert_eq!(overlapped.iter()
+                           .filter(|pattern| pattern.contains(x))
+                           .count(), (count > 1) as usize);
+                assert_eq!(uncovered.iter()
+                           .filter(|pattern| pattern.contains(x))
+                           .count(), (count == 0) as usize);
+            }
+            // No two patterns of a list make up a single pattern, as they
+            // would if they differed in just one fixed bit.
+            for list in [&uncovered, &overlapped] {
+                for a in list.iter() {
+                    assert!(list.iter().all(|b| {
+                        a.mask() != b.mask() ||
+                            (a.value() ^ b.value()).count_ones() != 1
+                    }), "{} merges with another pattern", a);
+                }
+            }
+        }
+    }
+    #[test]
+    fn too_many_runs_fail() {
+        let odd = MaskPattern::<u16>::new(1, 1);
+        assert_eq!(odd.to_ranges().map(|ranges| ranges.len()), Ok(1 << 15));
+        assert_eq!(MaskPattern::<u64>::new(1, 1).to_ranges(),
+                   Err(MaskLimitError));
+        let patterns = [MaskPattern::<u32>::new(1 << 12, 0),
+                        MaskPattern::new(1 << 12, 1 << 12)];
+        assert_eq!(masks_to_ranges(&patterns[..1]).map(|ranges| ranges.len()),
+                   Ok(1 << 19));
+        assert_eq!(masks_to_ranges(patterns.iter().chain(&patterns)),
+                   Err(MaskLimitError));
+    }
+}
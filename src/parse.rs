@@ -0,0 +1,600 @@
+//! Parsing of ranges and range lists.
+//!
+//! The parser accepts each notation that the crate can write (see
+//! `RangeStyle`), so the output of `Display` always parses back. English
+//! prose (the default) parses back to exactly the same ranges. The other
+//! notations cannot distinguish, for instance, `Bound(0u8, 5)` from `To(5u8)`,
+//! so they parse back to ranges covering the same values.
+//!
+//! Numbers may be decimal, hex (`0x`), octal (`0o`) or binary (`0b`), may
+//! contain underscores, and may carry a type suffix such as `u8`. The
+//! constants `MIN` and `MAX` may be written bare or with a type, as in
+//! `i32::MIN`. Type suffixes and type names must match the type being parsed.
+//!
+//! Values of `char` may also be written as character literals, like `'a'` or
+//! `'\u{7f}'`, or as code points, like `U+007F`.
+//!
+//! A range without values, such as `7-3`, `1..1` or `(3, 4)`, is rejected
+//! with `ParseErrorKind::EmptyRange`, so the parsed ranges are never empty.
+//! Such ranges are also the only ones whose `Display` does not parse back.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use super::{Int, IntRange, RangeSet};
+
+/// What went wrong while parsing.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ParseErrorKind {
+    /// A particular token, or a kind of token, was required.
+    Expected(&'static str),
+    /// A number has no digits, or digits invalid for its radix.
+    InvalidNumber,
+    /// A number is too large or too small for the type.
+    OutOfRange,
+    /// A type suffix or type name does not match the type being parsed.
+    WrongType,
+    /// A range has no values, because its end is below its start, or an
+    /// exclusive bound leaves nothing.
+    EmptyRange,
+}
+
+/// Error returned when a range or range list cannot be parsed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseRangeError {
+    /// Byte offset in the input at which the problem was found.
+    pub position: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseRangeError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        match self.kind {
+            ParseErrorKind::Expected(what) =>
+                write!(formatter, "expected {}", what)?,
+            ParseErrorKind::InvalidNumber =>
+                formatter.write_str("invalid number")?,
+            ParseErrorKind::OutOfRange =>
+                formatter.write_str("number out of range for type")?,
+            ParseErrorKind::WrongType =>
+                formatter.write_str("type does not match")?,
+            ParseErrorKind::EmptyRange =>
+                formatter.write_str("range is empty")?,
+        }
+        write!(formatter, " at position {}", self.position)
+    }
+}
+
+impl Error for ParseRangeError {}
+
+impl<T: Int> FromStr for IntRange<T> {
+    type Err = ParseRangeError;
+    fn from_str(s: &str) -> Result<Self, ParseRangeError> {
+        let mut parser = Parser{input: s, pos: 0};
+        let range = parser.parse_item()?;
+        parser.expect_end()?;
+        Ok(range)
+    }
+}
+
+impl<T: Int> FromStr for RangeSet<T> {
+    type Err = ParseRangeError;
+    fn from_str(s: &str) -> Result<Self, ParseRangeError> {
+        parse_ranges(s).map(|ranges| ranges.into_iter().collect())
+    }
+}
+
+/// Parses a list of ranges.
+///
+/// Ranges may be separated by `,`, `|` or `∪`, and the whole list may be
+/// enclosed in square brackets, as in `[4 and below, 7-9]`. An empty string,
+/// `[]` or `∅` gives an empty list. A list consisting of a single bracketed
+/// pair of numbers, like `[4, 7]`, is read as an interval.
+pub fn parse_ranges<T: Int>(s: &str)
+      -> Result<Vec<IntRange<T>>, ParseRangeError> {
+    let mut parser = Parser{input: s, pos: 0};
+    let mut ranges = Vec::new();
+    if parser.at_end() || parser.eat("∅") {
+        parser.expect_end()?;
+        return Ok(ranges);
+    }
+    let bracketed =
+        parser.rest().starts_with('[') && !parser.looks_like_interval::<T>();
+    if bracketed {
+        parser.eat("[");
+        if parser.eat("]") {
+            parser.expect_end()?;
+            return Ok(ranges);
+        }
+    }
+    loop {
+        ranges.push(parser.parse_item()?);
+        if !(parser.eat(",") || parser.eat("|") || parser.eat("∪")) {
+            break;
+        }
+    }
+    if bracketed {
+        parser.expect("]")?;
+    }
+    parser.expect_end()?;
+    Ok(ranges)
+}
+
+#[derive(Clone, Debug)]
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+    fn error<X>(&self, kind: ParseErrorKind) -> Result<X, ParseRangeError> {
+        Err(ParseRangeError{position: self.pos, kind})
+    }
+    // Consume a token if it is next, after any whitespace.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    // Consume a phrase if it is next, with any whitespace between its words.
+    fn eat_words(&mut self, words: &[&str]) -> bool {
+        let mut probe = self.clone();
+        for (i, word) in words.iter().enumerate() {
+            let before = probe.pos;
+            probe.skip_whitespace();
+            if (i > 0 && probe.pos == before) || !probe.eat(word) {
+                return false;
+            }
+        }
+        *self = probe;
+        true
+    }
+    fn expect(&mut self, token: &'static str) -> Result<(), ParseRangeError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            self.error(ParseErrorKind::Expected(token))
+        }
+    }
+    fn at_end(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest().is_empty()
+    }
+    fn expect_end(&mut self) -> Result<(), ParseRangeError> {
+        if self.at_end() {
+            Ok(())
+        } else {
+            self.error(ParseErrorKind::Expected("end of input"))
+        }
+    }
+    // Check whether a number or constant comes next.
+    fn at_value(&mut self) -> bool {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) => c == '-' || c == '\'' || c.is_ascii_alphanumeric(),
+            None => false,
+        }
+    }
+    // Consume a run of characters matching a predicate.
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+    // Check for an interval of the form `[a, b]` without consuming it.
+    fn looks_like_interval<T: Int>(&self) -> bool {
+        let mut probe = self.clone();
+        probe.parse_interval::<T>().is_ok()
+    }
+    // Parse a range, rejecting any that has no values.
+    fn parse_item<T: Int>(&mut self) -> Result<IntRange<T>, ParseRangeError> {
+        self.skip_whitespace();
+        let start_pos = self.pos;
+        match self.parse_range()? {
+            IntRange::Bound(start, end) if start > end =>
+                Err(ParseRangeError{position: start_pos,
+                                    kind: ParseErrorKind::EmptyRange}),
+            range => Ok(range),
+        }
+    }
+    fn parse_range<T: Int>(&mut self) -> Result<IntRange<T>, ParseRangeError> {
+        if self.eat_words(&["full", "range"]) || self.eat("_") {
+            return Ok(IntRange::Full);
+        }
+        self.skip_whitespace();
+        if self.rest().starts_with('[') || self.rest().starts_with('(') {
+            return self.parse_interval();
+        }
+        if self.eat("..=") {
+            return Ok(IntRange::To(self.parse_value()?));
+        }
+        if self.eat("..") {
+            return if self.at_value() {
+                self.parse_exclusive_end().map(IntRange::To)
+            } else {
+                Ok(IntRange::Full)
+            };
+        }
+        let start = self.parse_value()?;
+        if self.eat("..=") {
+            Ok(IntRange::Bound(start, self.parse_value()?))
+        } else if self.eat("..") {
+            if self.at_value() {
+                Ok(IntRange::Bound(start, self.parse_exclusive_end()?))
+            } else {
+                Ok(IntRange::From(start))
+            }
+        } else if self.eat_words(&["and", "below"]) {
+            Ok(IntRange::To(start))
+        } else if self.eat_words(&["and", "above"]) {
+            Ok(IntRange::From(start))
+        } else if self.eat("-") {
+            Ok(IntRange::Bound(start, self.parse_value()?))
+        } else {
+            Ok(IntRange::Bound(start, start))
+        }
+    }
+    // Parse `[a, b]`, where either bracket may be replaced by a parenthesis
+    // to exclude that end point.
+    fn parse_interval<T: Int>(&mut self)
+          -> Result<IntRange<T>, ParseRangeError> {
+        let exclude_start = if self.eat("(") {
+            true
+        } else {
+            self.expect("[")?;
+            false
+        };
+        self.skip_whitespace();
+        let start_pos = self.pos;
+        let mut start: T = self.parse_value()?;
+        self.expect(",")?;
+        let mut end: T = self.parse_value()?;
+        let exclude_end = if self.eat(")") {
+            true
+        } else {
+            self.expect("]")?;
+            false
+        };
+        if exclude_start {
+            start = match start.successor() {
+                Some(start) => start,
+                None => return Err(ParseRangeError{
+                    position: start_pos, kind: ParseErrorKind::EmptyRange}),
+            };
+        }
+        if exclude_end {
+            end = match end.predecessor() {
+                Some(end) => end,
+                None => return Err(ParseRangeError{
+                    position: start_pos, kind: ParseErrorKind::EmptyRange}),
+            };
+        }
+        Ok(IntRange::Bound(start, end))
+    }
+    // Parse the end of a half-open range, and convert it to an inclusive end.
+    fn parse_exclusive_end<T: Int>(&mut self) -> Result<T, ParseRangeError> {
+        self.skip_whitespace();
+        let end_pos = self.pos;
+        match self.parse_value::<T>()?.predecessor() {
+            Some(end) => Ok(end),
+            None => Err(ParseRangeError{position: end_pos,
+                                        kind: ParseErrorKind::EmptyRange}),
+        }
+    }
+    fn parse_value<T: Int>(&mut self) -> Result<T, ParseRangeError> {
+        self.skip_whitespace();
+        let start_pos = self.pos;
+        let out_of_range = Err(ParseRangeError{
+            position: start_pos, kind: ParseErrorKind::OutOfRange});
+        if self.rest().starts_with('\'') ||
+            (T::IS_CHAR && self.rest().starts_with("U+")) {
+            return self.parse_char();
+        }
+        let negative = self.eat("-");
+        match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() && !negative => {
+                let mut name = self.take_while(is_ident_char);
+                if self.rest().starts_with("::") {
+                    if name != T::NAME {
+                        return Err(ParseRangeError{
+                            position: start_pos,
+                            kind: ParseErrorKind::WrongType});
+                    }
+                    self.pos += 2;
+                    name = self.take_while(is_ident_char);
+                }
+                match name {
+                    "MIN" => Ok(T::min_value()),
+                    "MAX" => Ok(T::max_value()),
+                    _ => Err(ParseRangeError{
+                        position: start_pos,
+                        kind: ParseErrorKind::Expected("number")}),
+                }
+            },
+            Some(c) if c.is_ascii_digit() => {
+                let radix = if self.eat("0x") {
+                    16
+                } else if self.eat("0o") {
+                    8
+                } else if self.eat("0b") {
+                    2
+                } else {
+                    10
+                };
+                let digits = self.take_while(|c| c == '_' || c.is_digit(radix));
+                let mut magnitude = 0u128;
+                let mut any_digits = false;
+                for digit in digits.chars().filter_map(|c| c.to_digit(radix)) {
+                    any_digits = true;
+                    magnitude = match magnitude.checked_mul(radix as u128)
+                        .and_then(|x| x.checked_add(digit as u128)) {
+                        Some(magnitude) => magnitude,
+                        None => return out_of_range,
+                    };
+                }
+                if !any_digits {
+                    return self.error(ParseErrorKind::InvalidNumber);
+                }
+                let suffix_pos = self.pos;
+                let suffix = self.take_while(is_ident_char);
+                if !suffix.is_empty() && suffix != T::NAME {
+                    return Err(ParseRangeError{
+                        position: suffix_pos, kind: ParseErrorKind::WrongType});
+                }
+                match T::from_sign_magnitude(negative, magnitude) {
+                    Some(value) => Ok(value),
+                    None => out_of_range,
+                }
+            },
+            _ => self.error(ParseErrorKind::Expected("number")),
+        }
+    }
+    // Parse a character literal, such as `'a'` or `'\u{7f}'`, or a code
+    // point, such as `U+007F`.
+    fn parse_char<T: Int>(&mut self) -> Result<T, ParseRangeError> {
+        let start_pos = self.pos;
+        if !T::IS_CHAR {
+            return self.error(ParseErrorKind::WrongType);
+        }
+        let code = if self.eat("U+") {
+            self.parse_hex()?
+        } else {
+            self.pos += 1;
+            let code = match self.peek() {
+                Some('\\') => {
+                    self.pos += 1;
+                    self.parse_escape()?
+                },
+                Some(c) if c != '\'' => {
+                    self.pos += c.len_utf8();
+                    c as u32
+                },
+                _ => return self.error(ParseErrorKind::Expected("character")),
+            };
+            if !self.rest().starts_with('\'') {
+                return self.error(ParseErrorKind::Expected("'"));
+            }
+            self.pos += 1;
+            code
+        };
+        match T::from_sign_magnitude(false, code as u128) {
+            Some(value) => Ok(value),
+            None => Err(ParseRangeError{position: start_pos,
+                                        kind: ParseErrorKind::OutOfRange}),
+        }
+    }
+    // Parse the rest of an escape sequence in a character literal.
+    fn parse_escape(&mut self) -> Result<u32, ParseRangeError> {
+        let code = match self.peek() {
+            Some('n') => '\n' as u32,
+            Some('r') => '\r' as u32,
+            Some('t') => '\t' as u32,
+            Some('0') => 0,
+            Some(c @ ('\\' | '\'' | '"')) => c as u32,
+            Some('u') => {
+                self.pos += 1;
+                self.expect("{")?;
+                let code = self.parse_hex()?;
+                self.expect("}")?;
+                return Ok(code);
+            },
+            _ => return self.error(ParseErrorKind::Expected("escape sequence")),
+        };
+        self.pos += 1;
+        Ok(code)
+    }
+    // Parse the hex digits of a code point.
+    fn parse_hex(&mut self) -> Result<u32, ParseRangeError> {
+        let start_pos = self.pos;
+        let digits = self.take_while(|c| c.is_ascii_hexdigit());
+        if digits.is_empty() {
+            return self.error(ParseErrorKind::InvalidNumber);
+        }
+        u32::from_str_radix(digits, 16).map_err(|_| ParseRangeError{
+            position: start_pos, kind: ParseErrorKind::OutOfRange})
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c == '_' || c.is_ascii_alphanumeric()
+}
+
+#[cfg(test)]
+mod parse_tests {
+    use super::{ParseErrorKind, ParseRangeError, parse_ranges};
+    use super::super::{Int, IntRange, Notation, Radix, RangeList, RangeSet};
+    use super::super::RangeStyle;
+    fn error(position: usize, kind: ParseErrorKind) -> ParseRangeError {
+        ParseRangeError{position, kind}
+    }
+    #[test]
+    fn parse_prose() {
+        assert_eq!("7-9".parse(), Ok(IntRange::Bound(7i32, 9)));
+        assert_eq!("-5--1".parse(), Ok(IntRange::Bound(-5i32, -1)));
+        assert_eq!("4 and below".parse(), Ok(IntRange::To(4i32)));
+        assert_eq!("-4 and above".parse(), Ok(IntRange::From(-4i32)));
+        assert_eq!("full range".parse(), Ok(IntRange::<i32>::Full));
+        assert_eq!("12".parse(), Ok(IntRange::Bound(12i32, 12)));
+        assert_eq!("4 and  below".parse(), Ok(IntRange::To(4i32)));
+        assert_eq!("4\tand\tabove".parse(), Ok(IntRange::From(4i32)));
+        assert_eq!("full\nrange".parse(), Ok(IntRange::<i32>::Full));
+        assert_eq!("4 andbelow".parse::<IntRange<i32>>(),
+                   Err(error(2, ParseErrorKind::Expected("end of input"))));
+    }
+    #[test]
+    fn parse_patterns() {
+        assert_eq!("i32::MIN..=-1".parse(), Ok(IntRange::Bound(i32::MIN, -1)));
+        assert_eq!("10..=MAX".parse(), Ok(IntRange::Bound(10i32, i32::MAX)));
+        assert_eq!("..=5".parse(), Ok(IntRange::To(5u8)));
+        assert_eq!("5..".parse(), Ok(IntRange::From(5u8)));
+        assert_eq!("..".parse(), Ok(IntRange::<u8>::Full));
+        assert_eq!("_".parse(), Ok(IntRange::<u8>::Full));
+        assert_eq!("5..10".parse(), Ok(IntRange::Bound(5u8, 9)));
+        assert_eq!("..10".parse(), Ok(IntRange::To(9u8)));
+    }
+    #[test]
+    fn parse_intervals() {
+        assert_eq!("[0, 4]".parse(), Ok(IntRange::Bound(0u8, 4)));
+        assert_eq!("(0, 4)".parse(), Ok(IntRange::Bound(1u8, 3)));
+        assert_eq!("[-3,u8::MAX]".parse::<IntRange<i16>>(),
+                   Err(error(4, ParseErrorKind::WrongType)));
+        assert_eq!("[-3, i16::MAX)".parse(),
+                   Ok(IntRange::Bound(-3i16, i16::MAX - 1)));
+    }
+    #[test]
+    fn parse_literals() {
+        assert_eq!("0xff".parse(), Ok(IntRange::Bound(255u8, 255)));
+        assert_eq!("0o17..=0b1_0000".parse(), Ok(IntRange::Bound(15u8, 16)));
+        assert_eq!("1_000u16 and above".parse(), Ok(IntRange::From(1000u16)));
+        assert_eq!("-0x80i8".parse(), Ok(IntRange::Bound(-128i8, -128)));
+        assert_eq!(
+            "340282366920938463463374607431768211455".parse(),
+            Ok(IntRange::Bound(u128::MAX, u128::MAX)));
+    }
+    #[test]
+    fn parse_lists() {
+        let expected = vec![IntRange::To(4u8), IntRange::Bound(7, 9)];
+        assert_eq!(parse_ranges("[4 and below, 7-9]"), Ok(expected.clone()));
+        assert_eq!(parse_ranges("4 and below, 7-9"), Ok(expected.clone()));
+        assert_eq!(parse_ranges("..=4 | 7..=9"), Ok(expected));
+        assert_eq!(parse_ranges("[0, 4] ∪ [7, 9]"),
+                   Ok(vec![IntRange::Bound(0u8, 4), IntRange::Bound(7, 9)]));
+        assert_eq!(parse_ranges("[0, 4]"), Ok(vec![IntRange::Bound(0u8, 4)]));
+    }
+    #[test]
+    fn parse_empty_lists() {
+        assert_eq!(parse_ranges::<u8>(""), Ok(vec![]));
+        assert_eq!(parse_ranges::<u8>("  "), Ok(vec![]));
+        assert_eq!(parse_ranges::<u8>("[]"), Ok(vec![]));
+        assert_eq!(parse_ranges::<u8>("∅"), Ok(vec![]));
+    }
+    #[test]
+    fn parse_range_set() {
+        assert_eq!("0-9, 5-20".parse(),
+                   Ok(RangeSet::from(IntRange::Bound(0u8, 20))));
+    }
+    #[test]
+    fn errors_are_positioned() {
+        assert_eq!("256".parse::<IntRange<u8>>(),
+                   Err(error(0, ParseErrorKind::OutOfRange)));
+        assert_eq!("-1".parse::<IntRange<u8>>(),
+                   Err(error(0, ParseErrorKind::OutOfRange)));
+        assert_eq!("5 and beyond".parse::<IntRange<u8>>(),
+                   Err(error(2, ParseErrorKind::Expected("end of input"))));
+        assert_eq!("5u16".parse::<IntRange<u8>>(),
+                   Err(error(1, ParseErrorKind::WrongType)));
+        assert_eq!("0x".parse::<IntRange<u8>>(),
+                   Err(error(2, ParseErrorKind::InvalidNumber)));
+        assert_eq!("5..=".parse::<IntRange<u8>>(),
+                   Err(error(4, ParseErrorKind::Expected("number"))));
+        assert_eq!("..0".parse::<IntRange<u8>>(),
+                   Err(error(2, ParseErrorKind::EmptyRange)));
+        assert_eq!("1..1".parse::<IntRange<u8>>(),
+                   Err(error(0, ParseErrorKind::EmptyRange)));
+        assert_eq!("(3, 4)".parse::<IntRange<u8>>(),
+                   Err(error(0, ParseErrorKind::EmptyRange)));
+        assert_eq!(" 7-3".parse::<IntRange<u8>>(),
+                   Err(error(1, ParseErrorKind::EmptyRange)));
+        assert_eq!(parse_ranges::<u8>("0-1, 5..=4"),
+                   Err(error(5, ParseErrorKind::EmptyRange)));
+        assert_eq!(parse_ranges::<u8>("[1-2, 3-4"),
+                   Err(error(9, ParseErrorKind::Expected("]"))));
+        assert_eq!(parse_ranges::<u8>("1-2, 3-4, 300"),
+                   Err(error(10, ParseErrorKind::OutOfRange)));
+    }
+    #[test]
+    fn display_error() {
+        assert_eq!(format!("{}", "256".parse::<IntRange<u8>>().unwrap_err()),
+                   "number out of range for type at position 0");
+    }
+    fn round_trip<T: Int>(ranges: &[IntRange<T>]) {
+        // Prose reproduces the ranges exactly.
+        let text = format!("{}", RangeList::new(ranges));
+        assert_eq!(parse_ranges(&text).as_deref(), Ok(ranges), "{}", text);
+        for &range in ranges.iter() {
+            assert_eq!(format!("{}", range).parse(), Ok(range));
+        }
+        // Other styles reproduce the same values.
+        let expected: RangeSet<T> = ranges.iter().cloned().collect();
+        let notations = [Notation::Prose, Notation::Pattern,
+                         Notation::Interval];
+        let radixes = [Radix::Decimal, Radix::Binary, Radix::Octal,
+                       Radix::Hex];
+        for &notation in notations.iter() {
+            for &radix in radixes.iter() {
+                for &zero_pad in [false, true].iter() {
+                    let style = RangeStyle::new(notation).radix(radix)
+                        .zero_pad(zero_pad);
+                    let text = format!("{}", RangeList::new(ranges)
+                                       .style(style));
+                    assert_eq!(text.parse(), Ok(expected.clone()), "{}", text);
+                }
+            }
+        }
+    }
+    #[test]
+    fn display_round_trips() {
+        round_trip::<u8>(&[]);
+        round_trip(&[IntRange::To(4u8), IntRange::Bound(7, 9),
+                     IntRange::From(200)]);
+        round_trip(&[IntRange::<u8>::Full]);
+        round_trip(&[IntRange::To(-1i8), IntRange::Bound(-5, -1),
+                     IntRange::From(i8::MAX), IntRange::Bound(3, 3)]);
+        round_trip(&[IntRange::To(i128::MIN), IntRange::Bound(-1i128, 1),
+                     IntRange::From(i128::MAX)]);
+        round_trip(&[IntRange::Bound(0u128, u128::MAX - 1)]);
+        round_trip(&[IntRange::To('\t'), IntRange::Bound('\'', '\\'),
+                     IntRange::Bound('é', 'é'), IntRange::From('\u{E000}')]);
+    }
+    #[test]
+    fn parse_chars() {
+        assert_eq!("'a'..='z'".parse(), Ok(IntRange::Bound('a', 'z')));
+        assert_eq!("U+0041-U+005A".parse(), Ok(IntRange::Bound('A', 'Z')));
+        assert_eq!(r"'\u{D7FF}'..'\u{E001}'".parse(),
+                   Ok(IntRange::Bound('\u{D7FF}', '\u{E000}')));
+        assert_eq!(r"..'\n'".parse(), Ok(IntRange::To('\t')));
+        assert_eq!("char::MIN..=97".parse(), Ok(IntRange::Bound('\0', 'a')));
+        assert_eq!("U+D800".parse::<IntRange<char>>(),
+                   Err(error(0, ParseErrorKind::OutOfRange)));
+        assert_eq!("'ab'".parse::<IntRange<char>>(),
+                   Err(error(2, ParseErrorKind::Expected("'"))));
+        assert_eq!("'a'".parse::<IntRange<u8>>(),
+                   Err(error(0, ParseErrorKind::WrongType)));
+    }
+}
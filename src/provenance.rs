@@ -0,0 +1,176 @@
+//! Provenance of overlaps: which input ranges cover each overlapped segment.
+
+use std::collections::BTreeSet;
+use std::ops::RangeBounds;
+
+use super::{Int, IntRange, MergeRange};
+use super::convert::merge_range_from_bounds;
+use super::coverage::sweep;
+use super::MergeResult::*;
+
+/// An overlapped segment, together with the inputs that cover it.
+///
+/// `sources` holds the index (or key) of every input covering `range`, in the
+/// order in which those inputs were given.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Provenance<T: Int, K> {
+    pub range: IntRange<T>,
+    pub sources: Vec<K>,
+}
+
+/// Returns every overlapped segment of the input, with the indices of the
+/// input ranges that cover it.
+///
+/// Segments are disjoint and in ascending order. Neighboring segments are
+/// kept separate whenever they are covered by different sets of inputs, so
+/// every value in a segment is covered by exactly the listed inputs. Empty
+/// `Bound` ranges never cover anything.
+pub fn overlap_provenance<T, I>(ranges: I) -> Vec<Provenance<T, usize>>
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
+    overlap_segments(ranges.into_iter().map(|x| merge_range_from_bounds(&x)))
+        .into_iter()
+        .map(|(range, sources)| Provenance{
+            range: IntRange::from_merge_range(range),
+            sources,
+        })
+        .collect()
+}
+
+/// Like `overlap_provenance`, but labels each input range with a
+/// user-supplied key (for instance, a match arm name or line number) and
+/// reports the keys instead of indices.
+pub fn overlap_provenance_by_key<T, K, R, I>(ranges: I)
+      -> Vec<Provenance<T, K>>
+    where T: Int, K: Clone, R: RangeBounds<T>, I: IntoIterator<Item=(K, R)> {
+    let mut keys = Vec::new();
+    let merge_ranges = ranges.into_iter().map(|(key, range)| {
+        keys.push(key);
+        merge_range_from_bounds(&range)
+    });
+    overlap_segments(merge_ranges).into_iter()
+        .map(|(range, sources)| Provenance{
+            range: IntRange::from_merge_range(range),
+            sources: sources.into_iter().map(|i| keys[i].clone()).collect(),
+        })
+        .collect()
+}
+
+// Sweep over the range boundaries, and keep the segments where at least two
+// inputs are active. Empty input ranges are `None`, so that indices are
+// preserved.
+pub(crate) fn overlap_segments<T, I>(ranges: I)
+      -> Vec<(MergeRange<T>, Vec<usize>)>
+    where T: Int, I: Iterator<Item=Option<MergeRange<T>>> {
+    let mut segments = Vec::new();
+    sweep(ranges, |range, active| push_segment(&mut segments, active, range));
+    segments
+}
+
+// Record a segment if it is overlapped, merging it into the previous segment
+// if that has the same sources.
+fn push_segment<T: Int>(segments: &mut Vec<(MergeRange<T>, Vec<usize>)>,
+                        active: &BTreeSet<usize>, range: MergeRange<T>) {
+    if active.len() < 2 {
+        return;
+    }
+    if let Some(last) = segments.last_mut() {
+        if last.1.iter().eq(active.iter()) {
+            if let Adjacent(concat) = last.0.merge(range) {
+                last.0 = concat;
+                return;
+            }
+        }
+    }
+    segments.push((range, active.iter().cloned().collect()));
+}
+
+#[cfg(test)]
+mod provenance_tests {
+    use super::{Provenance, overlap_provenance, overlap_provenance_by_key};
+    use super::super::{IntRange, uncovered_and_overlapped};
+    #[test]
+    fn no_overlap_gives_nothing() {
+        assert_eq!(overlap_provenance([IntRange::To(5u8),
+                                        IntRange::From(6u8)]),
+                   vec![]);
+    }
+    #[test]
+    fn pair_overlap_reports_both() {
+        assert_eq!(overlap_provenance([
+            IntRange::Bound(0i32, 5),
+            IntRange::Bound(10i32, 20),
+            IntRange::From(3i32),
+            ]), vec![
+            Provenance{range: IntRange::Bound(3i32, 5), sources: vec![0, 2]},
+            Provenance{range: IntRange::Bound(10i32, 20), sources: vec![1, 2]},
+            ]);
+    }
+    #[test]
+    fn changing_sources_split_segments() {
+        // Input 0 overlaps input 1, then input 2 takes over from input 1 with
+        // no gap in between.
+        assert_eq!(overlap_provenance([
+            IntRange::Bound(0u8, 20),
+            IntRange::Bound(5u8, 9),
+            IntRange::Bound(10u8, 15),
+            ]), vec![
+            Provenance{range: IntRange::Bound(5u8, 9), sources: vec![0, 1]},
+            Provenance{range: IntRange::Bound(10u8, 15), sources: vec![0, 2]},
+            ]);
+    }
+    #[test]
+    fn multiway_overlap_lists_all_sources() {
+        assert_eq!(overlap_provenance([
+            IntRange::Bound(0u8, 10),
+            IntRange::Bound(6u8, 7),
+            IntRange::Bound(20u8, 30),
+            IntRange::Bound(5u8, 8),
+            ]), vec![
+            Provenance{range: IntRange::Bound(5u8, 5), sources: vec![0, 3]},
+            Provenance{range: IntRange::Bound(6u8, 7), sources: vec![0, 1, 3]},
+            Provenance{range: IntRange::Bound(8u8, 8), sources: vec![0, 3]},
+            ]);
+    }
+    #[test]
+    fn overlap_at_type_limits() {
+        assert_eq!(overlap_provenance([
+            IntRange::Full,
+            IntRange::From(i8::MAX),
+            IntRange::To(i8::MIN),
+            ]), vec![
+            Provenance{range: IntRange::To(i8::MIN), sources: vec![0, 2]},
+            Provenance{range: IntRange::From(i8::MAX), sources: vec![0, 1]},
+            ]);
+    }
+    #[test]
+    fn keys_replace_indices() {
+        assert_eq!(overlap_provenance_by_key(vec![
+            ("arm 3", IntRange::Bound(0u16, 7)),
+            ("arm 7", IntRange::Bound(6u16, 10)),
+            ]), vec![
+            Provenance{range: IntRange::Bound(6u16, 7),
+                       sources: vec!["arm 3", "arm 7"]},
+            ]);
+    }
+    #[test]
+    fn empty_bound_never_overlaps() {
+        assert_eq!(overlap_provenance([
+            IntRange::Bound(0u16, 7),
+            IntRange::Bound(6u16, 3),
+            ]), vec![]);
+    }
+    #[test]
+    fn segments_cover_overlap_set() {
+        let ranges = vec![
+            IntRange::Bound(6i8, 16),
+            IntRange::To(-10i8),
+            IntRange::From(15i8),
+            IntRange::Bound(4i8, 7),
+            ];
+        let (_, overlapped) = uncovered_and_overlapped(&ranges);
+        let segments: Vec<_> = overlap_provenance(&ranges).into_iter()
+            .map(|x| x.range).collect();
+        assert_eq!(segments, overlapped);
+    }
+}
@@ -0,0 +1,100 @@
+//! A complete report on a list of ranges, suitable for storing.
+
+use std::ops::{Bound, RangeBounds};
+
+use super::{Int, IntRange, Provenance, RangeCheck};
+use super::provenance::{overlap_provenance, overlap_provenance_by_key};
+
+/// The full result of checking a list of ranges: the uncovered and
+/// overlapped ranges, as returned by `uncovered_and_overlapped`, and the
+/// inputs responsible for each overlap, as returned by
+/// `overlap_provenance`.
+///
+/// With the `serde` feature enabled, reports can be serialized, for instance
+/// to keep them as build artifacts.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckReport<T: Int, K = usize> {
+    /// Ranges not covered by any input range.
+    pub uncovered: Vec<IntRange<T>>,
+    /// Ranges covered by more than one input range.
+    pub overlapped: Vec<IntRange<T>>,
+    /// The overlapped segments, with the inputs covering each one.
+    pub provenance: Vec<Provenance<T, K>>,
+}
+
+impl<T: Int> CheckReport<T> {
+    /// Checks the input ranges, identifying inputs by their index. The input
+    /// is accepted in the same forms as for `uncovered_and_overlapped`.
+    pub fn new<I>(ranges: I) -> Self
+        where I: IntoIterator, I::Item: RangeBounds<T> {
+        let bounds: Vec<_> = ranges.into_iter().map(to_bounds).collect();
+        let check = RangeCheck::new(bounds.iter().copied());
+        CheckReport{
+            uncovered: check.uncovered().collect(),
+            overlapped: check.overlapped().ranges(),
+            provenance: overlap_provenance(bounds),
+        }
+    }
+}
+
+impl<T: Int, K: Clone> CheckReport<T, K> {
+    /// Like `new`, but identifies inputs by a user-supplied key, as
+    /// `overlap_provenance_by_key` does.
+    pub fn by_key<R, I>(ranges: I) -> Self
+        where R: RangeBounds<T>, I: IntoIterator<Item=(K, R)> {
+        let keyed: Vec<_> = ranges.into_iter()
+            .map(|(key, range)| (key, to_bounds(range)))
+            .collect();
+        let check = RangeCheck::new(keyed.iter().map(|x| x.1));
+        CheckReport{
+            uncovered: check.uncovered().collect(),
+            overlapped: check.overlapped().ranges(),
+            provenance: overlap_provenance_by_key(keyed),
+        }
+    }
+    /// Returns true if every value of the type is covered.
+    pub fn is_exhaustive(&self) -> bool {
+        self.uncovered.is_empty()
+    }
+    /// Returns true if no value is covered more than once.
+    pub fn is_disjoint(&self) -> bool {
+        self.overlapped.is_empty()
+    }
+}
+
+// Copy out the bounds of a range, so that the input can be read twice.
+fn to_bounds<T: Int, R: RangeBounds<T>>(range: R) -> (Bound<T>, Bound<T>) {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+#[cfg(test)]
+mod report_tests {
+    use super::CheckReport;
+    use super::super::{IntRange, Provenance};
+    #[test]
+    fn report_by_index() {
+        let report = CheckReport::new([
+            IntRange::Bound(0u8, 10),
+            IntRange::Bound(5u8, 20),
+            IntRange::From(200u8),
+            ]);
+        assert_eq!(report, CheckReport{
+            uncovered: vec![IntRange::Bound(21u8, 199)],
+            overlapped: vec![IntRange::Bound(5u8, 10)],
+            provenance: vec![Provenance{range: IntRange::Bound(5u8, 10),
+                                        sources: vec![0, 1]}],
+        });
+        assert!(!report.is_exhaustive());
+        assert!(!report.is_disjoint());
+    }
+    #[test]
+    fn report_by_key() {
+        let report = CheckReport::by_key([("low", IntRange::To(0i8)),
+                                          ("high", IntRange::From(0))]);
+        assert!(report.is_exhaustive());
+        assert_eq!(report.provenance, vec![
+            Provenance{range: IntRange::Bound(0i8, 0),
+                       sources: vec!["low", "high"]}]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Serde support, enabled by the `serde` feature.
+//!
+//! By default, an `IntRange` is written in an explicit form, as a struct
+//! with `start` and `end` fields. A side on which the range is unbounded is
+//! written as `null` (or left out), so that `7-9` is `{"start": 7, "end": 9}`
+//! in JSON and `4 and below` is `{"start": null, "end": 4}`. A `RangeSet` is
+//! written as a sequence of such ranges.
+//!
+//! The `compact` module writes ranges and range sets as strings instead,
+//! like `"7-9"` or `"[4 and below, 7-9]"`. Select it for a field with
+//! `#[serde(with = "int_range_check::serde_forms::compact")]`. In
+//! human-readable formats, a plain `IntRange` accepts either form when it is
+//! deserialized.
+//!
+//! Deserialization rejects a range whose start is above its end, just as
+//! `Validation::Strict` does, rather than reading it as an empty range.
+
+use std::fmt::{self, Display, Formatter};
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::de::value::MapAccessDeserializer;
+use serde::ser::{Serialize, Serializer};
+
+use super::{Int, IntRange, RangeSet, Validation, parse_ranges,
+            validate_ranges};
+
+impl<T: Int + Serialize> Serialize for IntRange<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+          -> Result<S::Ok, S::Error> {
+        explicit::serialize(self, serializer)
+    }
+}
+
+impl<'de, T: Int + Deserialize<'de>> Deserialize<'de> for IntRange<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+          -> Result<Self, D::Error> {
+        // Only self-describing formats can say which form they hold, and
+        // the human-readable ones are assumed to be self-describing.
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(EitherVisitor(PhantomData))
+        } else {
+            explicit::deserialize(deserializer)
+        }
+    }
+}
+
+impl<T: Int + Serialize> Serialize for RangeSet<T> {
+    fn serialize<S: Serializer>(&self, serializer: S)
+          -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Int + Deserialize<'de>> Deserialize<'de> for RangeSet<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D)
+          -> Result<Self, D::Error> {
+        let ranges = Vec::<IntRange<T>>::deserialize(deserializer)?;
+        Ok(ranges.into_iter().collect())
+    }
+}
+
+/// Serializes ranges as `{start, end}` structs. This is the default form for
+/// `IntRange`, but this module may be used to insist on it.
+pub mod explicit {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::check_order;
+    use super::super::{Int, IntRange};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "IntRange", deny_unknown_fields)]
+    struct Explicit<T> {
+        start: Option<T>,
+        end: Option<T>,
+    }
+
+    /// Serializes a range in the explicit form.
+    pub fn serialize<T, S>(range: &IntRange<T>, serializer: S)
+          -> Result<S::Ok, S::Error>
+        where T: Int + Serialize, S: Serializer {
+        let (start, end) = match *range {
+            IntRange::Bound(start, end) => (Some(start), Some(end)),
+            IntRange::To(end) => (None, Some(end)),
+            IntRange::From(start) => (Some(start), None),
+            IntRange::Full => (None, None),
+        };
+        Explicit{start, end}.serialize(serializer)
+    }
+
+    /// Deserializes a range in the explicit form, rejecting reversed bounds.
+    pub fn deserialize<'de, T, D>(deserializer: D)
+          -> Result<IntRange<T>, D::Error>
+        where T: Int + Deserialize<'de>, D: Deserializer<'de> {
+        let Explicit{start, end} = Explicit::deserialize(deserializer)?;
+        check_order(match (start, end) {
+            (Some(start), Some(end)) => IntRange::Bound(start, end),
+            (None, Some(end)) => IntRange::To(end),
+            (Some(start), None) => IntRange::From(start),
+            (None, None) => IntRange::Full,
+        })
+    }
+}
+
+/// Serializes ranges and range sets as strings, in the prose notation used
+/// by `Display`. Deserialization accepts any notation that `FromStr` does.
+pub mod compact {
+    use std::fmt::{self, Formatter};
+    use std::marker::PhantomData;
+
+    use serde::de::{self, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use super::{CompactForm, sealed};
+
+    /// Serializes a value as a string.
+    pub fn serialize<R, S>(value: &R, serializer: S)
+          -> Result<S::Ok, S::Error>
+        where R: CompactForm, S: Serializer {
+        serializer.collect_str(value)
+    }
+
+    /// Deserializes a value from a string, rejecting reversed bounds.
+    pub fn deserialize<'de, R, D>(deserializer: D) -> Result<R, D::Error>
+        where R: CompactForm, D: Deserializer<'de> {
+        deserializer.deserialize_str(StrVisitor(PhantomData))
+    }
+
+    pub(super) struct StrVisitor<R>(pub(super) PhantomData<R>);
+
+    impl<R: CompactForm> Visitor<'_> for StrVisitor<R> {
+        type Value = R;
+        fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+            formatter.write_str("a range string")
+        }
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<R, E> {
+            sealed::FromCompact::from_compact(s)
+        }
+    }
+}
+
+mod sealed {
+    pub trait FromCompact: Sized {
+        fn from_compact<E: serde::de::Error>(s: &str) -> Result<Self, E>;
+    }
+}
+
+/// Types with a compact string form: `IntRange` and `RangeSet`.
+///
+/// This trait is sealed, and only exists to bound the functions in
+/// `compact`.
+pub trait CompactForm: Display + sealed::FromCompact {}
+
+impl<T: Int> CompactForm for IntRange<T> {}
+
+impl<T: Int> sealed::FromCompact for IntRange<T> {
+    fn from_compact<E: de::Error>(s: &str) -> Result<Self, E> {
+        check_order(s.parse().map_err(E::custom)?)
+    }
+}
+
+impl<T: Int> CompactForm for RangeSet<T> {}
+
+impl<T: Int> sealed::FromCompact for RangeSet<T> {
+    fn from_compact<E: de::Error>(s: &str) -> Result<Self, E> {
+        let ranges: Vec<IntRange<T>> = parse_ranges(s).map_err(E::custom)?;
+        let ranges = validate_ranges(ranges, Validation::Strict)
+            .map_err(E::custom)?;
+        Ok(ranges.into_iter().collect())
+    }
+}
+
+// Reject a range with reversed bounds, which the constructors would treat
+// as empty.
+fn check_order<T: Int, E: de::Error>(range: IntRange<T>)
+      -> Result<IntRange<T>, E> {
+    match range {
+        IntRange::Bound(start, end) if start > end =>
+            Err(E::custom(format_args!(
+                "range {:?}..={:?} has its bounds reversed", start, end))),
+        _ => Ok(range),
+    }
+}
+
+// Reads a range in either the explicit or the compact form.
+struct EitherVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Int + Deserialize<'de>> Visitor<'de> for EitherVisitor<T> {
+    type Value = IntRange<T>;
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a range string or a map with start and end")
+    }
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<IntRange<T>, E> {
+        compact::StrVisitor(PhantomData).visit_str(s)
+    }
+    fn visit_map<M: MapAccess<'de>>(self, map: M)
+          -> Result<IntRange<T>, M::Error> {
+        explicit::deserialize(MapAccessDeserializer::new(map))
+    }
+}
+
+#[cfg(test)]
+mod serde_tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{from_str, json, to_string, to_value};
+    use super::super::{CheckReport, DomainCheck, IntRange, RangeSet,
+                       check_in_domain};
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Artifact {
+        #[serde(with = "super::compact")]
+        range: IntRange<i8>,
+        #[serde(with = "super::compact")]
+        set: RangeSet<u16>,
+    }
+    #[test]
+    fn explicit_form_by_default() {
+        assert_eq!(to_value(IntRange::Bound(7u8, 9)).unwrap(),
+                   json!({"start": 7, "end": 9}));
+        assert_eq!(to_value(IntRange::To(-4i32)).unwrap(),
+                   json!({"start": null, "end": -4}));
+        assert_eq!(to_value(IntRange::<u64>::Full).unwrap(),
+                   json!({"start": null, "end": null}));
+    }
+    #[test]
+    fn explicit_form_round_trip() {
+        for range in [IntRange::Bound(-3i64, 3), IntRange::To(i64::MIN),
+                      IntRange::From(5), IntRange::Full] {
+            let json = to_string(&range).unwrap();
+            assert_eq!(from_str::<IntRange<i64>>(&json).unwrap(), range);
+        }
+        assert_eq!(from_str::<IntRange<u8>>(r#"{"end": 4}"#).unwrap(),
+                   IntRange::To(4));
+    }
+    #[test]
+    fn compact_form() {
+        let artifact = Artifact{
+            range: IntRange::To(4),
+            set: [IntRange::Bound(7, 9), IntRange::From(100)].into_iter()
+                .collect(),
+        };
+        let json = to_string(&artifact).unwrap();
+        assert_eq!(json,
+                   r#"{"range":"4 and below","set":"[7-9, 100 and above]"}"#);
+        assert_eq!(from_str::<Artifact>(&json).unwrap(), artifact);
+    }
+    #[test]
+    fn either_form_accepted() {
+        assert_eq!(from_str::<IntRange<u8>>(r#""0x10..=0x1f""#).unwrap(),
+                   IntRange::Bound(16, 31));
+        assert_eq!(from_str::<Vec<IntRange<u8>>>(
+            r#"["7-9", {"start": 200}]"#).unwrap(),
+                   vec![IntRange::Bound(7, 9), IntRange::From(200)]);
+    }
+    #[test]
+    fn reversed_bounds_rejected() {
+        let error = from_str::<IntRange<u8>>(r#"{"start": 5, "end": 1}"#)
+            .unwrap_err();
+        assert!(error.to_string()
+                .starts_with("range 5..=1 has its bounds reversed"));
+        assert!(from_str::<IntRange<u8>>(r#""5-1""#).is_err());
+        assert!(from_str::<Artifact>(r#"{"range": "1", "set": "[5-1]"}"#)
+                .is_err());
+        assert!(from_str::<RangeSet<u8>>(r#"[{"start": 9, "end": 8}]"#)
+                .is_err());
+        assert!(from_str::<IntRange<u8>>(r#"{"start": 300}"#).is_err());
+        assert!(from_str::<IntRange<u8>>(r#"{"begin": 3}"#).is_err());
+    }
+    #[test]
+    fn range_set_is_normalized() {
+        let set: RangeSet<u8> = from_str(
+            r#"[{"start": 5, "end": 9}, {"start": 0, "end": 6}]"#).unwrap();
+        assert_eq!(set.ranges(), vec![IntRange::To(9u8)]);
+        assert_eq!(to_value(&set).unwrap(),
+                   json!([{"start": null, "end": 9}]));
+    }
+    #[test]
+    fn reports_round_trip() {
+        let report = CheckReport::by_key([("a", IntRange::Bound(0i8, 10)),
+                                          ("b", IntRange::From(5))]);
+        assert_eq!(to_value(&report).unwrap(), json!({
+            "uncovered": [{"start": null, "end": -1}],
+            "overlapped": [{"start": 5, "end": 10}],
+            "provenance": [{"range": {"start": 5, "end": 10},
+                            "sources": ["a", "b"]}],
+        }));
+        let json = to_string(&report).unwrap();
+        assert_eq!(from_str::<CheckReport<i8, String>>(&json).unwrap(),
+                   CheckReport::by_key([("a".to_string(),
+                                         IntRange::Bound(0i8, 10)),
+                                        ("b".to_string(),
+                                         IntRange::From(5))]));
+        let domain: RangeSet<u8> = IntRange::Bound(0, 9).into();
+        let check = check_in_domain([IntRange::Bound(5u8, 20)], &domain);
+        let json = to_string(&check).unwrap();
+        assert_eq!(from_str::<DomainCheck<u8>>(&json).unwrap(), check);
+    }
+}
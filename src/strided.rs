@@ -0,0 +1,796 @@
+//! Strided ranges, which take every `step`th value, and sets of values built
+//! from them, such as the values congruent to 1 modulo 4.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+use super::{Int, IntRange};
+
+/// The values `start`, `start + step`, `start + 2 * step` and so on, up to
+/// `end`. A plain range is a strided range with a step of 1.
+///
+/// For `char`, values are counted by their position among the Unicode scalar
+/// values, so strides skip over the surrogate code points.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StridedRange<T: Int> {
+    start: T,
+    end: T,
+    step: u128,
+}
+
+impl<T: Int> StridedRange<T> {
+    /// Creates a strided range. The end is lowered to the last value that
+    /// the steps reach, so equal ranges compare equal. The range is empty if
+    /// `start` is above `end`, and all empty ranges compare equal too.
+    ///
+    /// Panics if `step` is zero.
+    pub fn new(start: T, end: T, step: u128) -> Self {
+        assert!(step > 0, "step must be positive");
+        if start > end {
+            return StridedRange{
+                start: T::max_value(),
+                end: T::min_value(),
+                step: 1,
+            };
+        }
+        let (start_offset, end_offset) = (offset(start), offset(end));
+        let last = start_offset + (end_offset - start_offset) / step * step;
+        StridedRange{start, end: from_offset(last), step}
+    }
+    /// Returns the values of `range` that are congruent to `residue` modulo
+    /// `modulus`, as `x % 4 == 1` selects for a modulus of 4, or `None` if
+    /// there are none. Negative values are taken modulo `modulus` as by
+    /// `rem_euclid`.
+    ///
+    /// Panics if `modulus` is zero.
+    pub fn congruent(range: IntRange<T>, modulus: u128, residue: u128)
+          -> Option<Self> {
+        assert!(modulus > 0, "modulus must be positive");
+        let merge_range = range.to_merge_range()?;
+        let residue = residue % modulus;
+        let current = residue_of(merge_range.start, modulus);
+        let delta = if residue >= current {
+            residue - current
+        } else {
+            modulus - (current - residue)
+        };
+        let start = offset(merge_range.start).checked_add(delta)?;
+        if start > offset(merge_range.end) {
+            return None;
+        }
+        Some(StridedRange::new(from_offset(start), merge_range.end, modulus))
+    }
+    /// Returns the first value of the range.
+    pub fn start(&self) -> T {
+        self.start
+    }
+    /// Returns the last value of the range.
+    pub fn end(&self) -> T {
+        self.end
+    }
+    /// Returns the distance between consecutive values of the range.
+    pub fn step(&self) -> u128 {
+        self.step
+    }
+    /// Returns true if the range contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+    /// Returns true if the range contains `value`.
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value <= self.end &&
+            (offset(value) - offset(self.start)).is_multiple_of(self.step)
+    }
+}
+
+impl<T: Int> From<IntRange<T>> for StridedRange<T> {
+    /// Converts a plain range to a strided range with a step of 1.
+    fn from(range: IntRange<T>) -> Self {
+        match range {
+            IntRange::Bound(start, end) => StridedRange::new(start, end, 1),
+            IntRange::To(end) => StridedRange::new(T::min_value(), end, 1),
+            IntRange::From(start) =>
+                StridedRange::new(start, T::max_value(), 1),
+            IntRange::Full =>
+                StridedRange::new(T::min_value(), T::max_value(), 1),
+        }
+    }
+}
+
+impl<T: Int> Display for StridedRange<T> {
+    /// Writes the range like an `IntRange::Bound`, followed by the step if it
+    /// is not 1, as in `1-253 step 4`.
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "{}", IntRange::Bound(self.start, self.end))?;
+        if self.step > 1 && self.start < self.end {
+            write!(formatter, " step {}", self.step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when strided ranges are too irregular to work with, because
+/// their steps have large prime factors and the result would need too many
+/// pieces.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StrideLimitError;
+
+impl Display for StrideLimitError {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "strided ranges need more than {} congruence \
+                           classes", CLASS_LIMIT)
+    }
+}
+
+impl Error for StrideLimitError {}
+
+// The most congruence classes that one operation looks at.
+const CLASS_LIMIT: usize = 1 << 20;
+
+/// A set of values made of strided ranges, stored as disjoint strided ranges
+/// in ascending order of their starts.
+///
+/// The type is cut into segments between the ends of the ranges, and each
+/// segment is split into congruence classes, one prime factor of the steps at
+/// a time, until each class is either inside or outside of every range.
+/// Steps with small prime factors, as used to split values by parity or
+/// alignment, need few classes, even if the steps are large. So do ranges
+/// with few values, which are taken value by value. A range with many values
+/// and a step with a large prime factor may need too many classes, and then
+/// the operation fails with `StrideLimitError`.
+#[derive(Clone, Debug)]
+pub struct CongruenceSet<T: Int> {
+    ranges: Vec<StridedRange<T>>,
+}
+
+impl<T: Int> CongruenceSet<T> {
+    /// Creates the union of a list of strided ranges.
+    pub fn new<I>(ranges: I) -> Result<Self, StrideLimitError>
+        where I: IntoIterator, I::Item: Borrow<StridedRange<T>> {
+        let ranges: Vec<StridedRange<T>> =
+            ranges.into_iter().map(|range| *range.borrow()).collect();
+        Ok(CongruenceSet{ranges: classify(&ranges, |depth| depth > 0)?})
+    }
+    /// Returns true if the set contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+    /// Returns true if the set contains every value of the type.
+    pub fn is_full(&self) -> bool {
+        // A full segment is never split, and plain neighbors are joined.
+        self.ranges == [StridedRange::from(IntRange::Full)]
+    }
+    /// Returns true if `value` is in the set.
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges.iter().any(|range| range.contains(value))
+    }
+    /// Returns the set of values of the type that are not in `self`.
+    pub fn complement(&self) -> Result<Self, StrideLimitError> {
+        Ok(CongruenceSet{ranges: classify(&self.ranges, |depth| depth == 0)?})
+    }
+    /// Returns the disjoint strided ranges making up the set, in ascending
+    /// order of their starts.
+    ///
+    /// Classes whose every subclass is in the set are kept whole, ranges
+    /// continuing one another are joined, and ranges that interleave are
+    /// merged into one with a coarser step, as `0-252 step 6` and `3-255
+    /// step 6` are into `0-255 step 3`. So no range of the list continues
+    /// another, and no ranges with the same step make up a single range.
+    pub fn ranges(&self) -> Vec<StridedRange<T>> {
+        self.ranges.clone()
+    }
+}
+
+/// The strided version of `uncovered_and_overlapped`. Returns:
+///
+///  1) a list of strided ranges covering the values which are not covered by
+///     any input range, and
+///
+///  2) a list of strided ranges covering the values which are covered by
+///     more than one input range.
+///
+/// Plain ranges may be mixed in by converting them with `From`. Both lists
+/// are written as by `CongruenceSet::ranges`, with no range continuing
+/// another or interleaving with others into a single range. Empty input
+/// ranges are ignored.
+///
+/// Fails with `StrideLimitError` if the steps have prime factors so large
+/// that the lists would need too many pieces, as described for
+/// `CongruenceSet`.
+#[allow(clippy::type_complexity)]
+pub fn uncovered_and_overlapped_strided<T, I>(ranges: I)
+      -> Result<(Vec<StridedRange<T>>, Vec<StridedRange<T>>), StrideLimitError>
+    where T: Int, I: IntoIterator, I::Item: Borrow<StridedRange<T>> {
+    let ranges: Vec<StridedRange<T>> =
+        ranges.into_iter().map(|range| *range.borrow()).collect();
+    Ok((classify(&ranges, |depth| depth == 0)?,
+        classify(&ranges, |depth| depth > 1)?))
+}
+
+// Find the values whose depth, the number of input ranges covering them
+// counted up to 2, satisfies `keep`. The result is written as disjoint
+// strided ranges in ascending order of their starts.
+fn classify<T, F>(ranges: &[StridedRange<T>], keep: F)
+      -> Result<Vec<StridedRange<T>>, StrideLimitError>
+    where T: Int, F: Fn(usize) -> bool {
+    let mut refiner = Refiner{keep, budget: CLASS_LIMIT, pieces: Vec::new()};
+    let mut offsets = Vec::new();
+    for range in ranges.iter().filter(|range| !range.is_empty()) {
+        let (start, end) = (offset(range.start), offset(range.end));
+        let more = (end - start) / range.step;
+        // A range with fewer values than it would take classes to split
+        // them off is taken value by value.
+        if more > 0 && more < smallest_factor(range.step) {
+            refiner.spend(more + 1)?;
+            offsets.extend((0..=more).map(|j| {
+                let x = start + j * range.step;
+                (x, x, 1)
+            }));
+        } else {
+            offsets.push((start, end, range.step));
+        }
+    }
+    let ranges = offsets;
+    // Cut the type into segments that every input range either spans or
+    // misses.
+    let max_offset = offset(T::max_value());
+    let mut cuts = vec![0];
+    for &(start, end, _) in ranges.iter() {
+        cuts.push(start);
+        if end < max_offset {
+            cuts.push(end + 1);
+        }
+    }
+    cuts.sort();
+    cuts.dedup();
+    let mut joiner = Joiner::default();
+    for (i, &start) in cuts.iter().enumerate() {
+        let end = match cuts.get(i + 1) {
+            Some(&next) => next - 1,
+            None => max_offset,
+        };
+        // Within the segment, each spanning range is a congruence class.
+        let spanning: Vec<Class> = ranges.iter()
+            .filter(|&&(range_start, range_end, _)| {
+                range_start <= start && end <= range_end
+            })
+            .map(|&(range_start, _, step)| Class{
+                residue: range_start % step,
+                modulus: step,
+            })
+            .collect();
+        refiner.pieces.clear();
+        let whole = Class{residue: 0, modulus: 1};
+        let segment = (start, end);
+        if let Node::Kept(true) =
+            refiner.refine(segment, whole, 0, &spanning)? {
+            refiner.pieces.push((start, end, 1));
+        }
+        refiner.pieces.sort_by_key(|piece| piece.0);
+        for &(start, end, step) in refiner.pieces.iter() {
+            joiner.push(start, end, step);
+        }
+    }
+    let mut pieces = joiner.ranges;
+    pieces.sort_by_key(|piece| piece.0);
+    while coarsen(&mut pieces) {
+        let mut joiner = Joiner::default();
+        for &(start, end, step) in pieces.iter() {
+            joiner.push(start, end, step);
+        }
+        pieces = joiner.ranges;
+        pieces.sort_by_key(|piece| piece.0);
+    }
+    Ok(pieces.into_iter()
+       .map(|(start, end, step)| StridedRange{
+           start: from_offset(start),
+           end: from_offset(end),
+           step: if start == end { 1 } else { step },
+       })
+       .collect())
+}
+
+// Merge ranges that interleave into one with a coarser step: the ranges
+// taking `r + k * s` modulo `p * s`, for each `k` below a prime `p`, into one
+// taking `r` modulo `s`, if together they take every such value from the
+// lowest start to the highest end. Ranges must come in ascending order of
+// their starts, and stay so. Returns true if any were merged.
+fn coarsen(pieces: &mut Vec<(u128, u128, u128)>) -> bool {
+    let mut by_start: HashMap<(u128, u128), usize> = pieces.iter()
+        .enumerate()
+        .filter(|&(_, &(start, end, _))| start < end)
+        .map(|(i, &(start, _, step))| ((start, step), i))
+        .collect();
+    let mut merged = vec![false; pieces.len()];
+    let mut changed = false;
+    for i in 0..pieces.len() {
+        let (start, _, step) = pieces[i];
+        if merged[i] || by_start.get(&(start, step)) != Some(&i) {
+            continue;
+        }
+        let mut rest = step;
+        while rest > 1 {
+            let prime = smallest_factor(rest);
+            while rest.is_multiple_of(prime) {
+                rest /= prime;
+            }
+            if prime > pieces.len() as u128 {
+                continue;
+            }
+            let fine = step / prime;
+            let members: Option<Vec<usize>> = (0..prime)
+                .map(|k| by_start.get(&(start + k * fine, step)).copied())
+                .collect();
+            let members = match members {
+                Some(members) => members,
+                None => continue,
+            };
+            let mut ends: Vec<u128> =
+                members.iter().map(|&j| pieces[j].1).collect();
+            ends.sort();
+            let last = ends[ends.len() - 1];
+            let interleaved = ends.iter().rev().enumerate()
+                .all(|(k, &end)| end == last - k as u128 * fine);
+            if !interleaved {
+                continue;
+            }
+            for &j in members.iter() {
+                by_start.remove(&(pieces[j].0, step));
+                merged[j] = true;
+            }
+            merged[i] = false;
+            pieces[i] = (start, last, fine);
+            changed = true;
+            break;
+        }
+    }
+    let mut i = 0;
+    pieces.retain(|_| {
+        i += 1;
+        !merged[i - 1]
+    });
+    changed
+}
+
+// The offsets congruent to `residue` modulo `modulus`.
+#[derive(Clone, Copy, Debug)]
+struct Class {
+    residue: u128,
+    modulus: u128,
+}
+
+impl Class {
+    // Find the first offset of the class in a segment, and how many more
+    // follow it there, if any.
+    fn span(self, (start, end): (u128, u128)) -> Option<(u128, u128)> {
+        let start_residue = start % self.modulus;
+        let delta = if self.residue >= start_residue {
+            self.residue - start_residue
+        } else {
+            self.modulus - (start_residue - self.residue)
+        };
+        let first = start.checked_add(delta).filter(|&first| first <= end)?;
+        Some((first, (end - first) / self.modulus))
+    }
+    fn contains(self, x: u128) -> bool {
+        x % self.modulus == self.residue
+    }
+    fn is_superset(self, other: Class) -> bool {
+        other.modulus.is_multiple_of(self.modulus) &&
+            other.residue % self.modulus == self.residue
+    }
+    fn intersects(self, other: Class) -> bool {
+        let divisor = gcd(self.modulus, other.modulus);
+        self.residue % divisor == other.residue % divisor
+    }
+}
+
+// What a class holds after refinement: no values in the segment, values that
+// are all kept or all dropped, or a mixture, whose kept pieces have been
+// recorded already.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Node {
+    Empty,
+    Kept(bool),
+    Mixed,
+}
+
+// Splits the classes of a segment until each is inside or outside of every
+// input range, recording the kept pieces as (start, end, step).
+struct Refiner<F> {
+    keep: F,
+    budget: usize,
+    pieces: Vec<(u128, u128, u128)>,
+}
+
+impl<F: Fn(usize) -> bool> Refiner<F> {
+    // Refine a class that `depth` inputs contain, and that `inputs`
+    // may intersect.
+    fn refine(&mut self, segment: (u128, u128), class: Class, depth: usize,
+              inputs: &[Class]) -> Result<Node, StrideLimitError> {
+        self.spend(1)?;
+        let (first, more) = match class.span(segment) {
+            Some(span) => span,
+            None => return Ok(Node::Empty),
+        };
+        let mut depth = depth;
+        let mut partial = Vec::new();
+        for &input in inputs {
+            if input.is_superset(class) {
+                depth += 1;
+            } else if input.intersects(class) {
+                partial.push(input);
+            }
+        }
+        let depth = depth.min(2);
+        // More inputs can only raise the depth.
+        if partial.is_empty() || depth == 2 ||
+            (depth == 1 && (self.keep)(1) == (self.keep)(2)) {
+            return Ok(Node::Kept((self.keep)(depth)));
+        }
+        // Split on a prime factor of what the first partial input needs
+        // beyond the modulus of the class.
+        let needed = partial[0].modulus / gcd(class.modulus,
+                                              partial[0].modulus);
+        let factor = smallest_factor(needed);
+        let mut children = Vec::new();
+        match class.modulus.checked_mul(factor) {
+            Some(modulus) if more >= factor => {
+                self.spend(factor)?;
+                for k in 0..factor {
+                    let child = Class{
+                        residue: class.residue + k * class.modulus,
+                        modulus,
+                    };
+                    let node = self.refine(segment, child, depth, &partial)?;
+                    if let Some((first, more)) = child.span(segment) {
+                        let last = first + more * modulus;
+                        children.push(((first, last, modulus), node));
+                    }
+                }
+            },
+            // Each value of the class is apart from the others.
+            _ => {
+                self.spend(more)?;
+                for j in 0..=more {
+                    let x = first + j * class.modulus;
+                    let depth = depth + partial.iter()
+                        .filter(|input| input.contains(x))
+                        .count();
+                    children.push(((x, x, 1),
+                                   Node::Kept((self.keep)(depth.min(2)))));
+                }
+            },
+        }
+        let mut kept = None;
+        for &(_, node) in children.iter() {
+            match (node, kept) {
+                (Node::Empty, _) => {},
+                (Node::Kept(x), None) => kept = Some(x),
+                (Node::Kept(x), Some(y)) if x == y => {},
+                _ => {
+                    kept = None;
+                    break;
+                },
+            }
+        }
+        if let Some(x) = kept {
+            return Ok(Node::Kept(x));
+        }
+        for &(piece, node) in children.iter() {
+            if node == Node::Kept(true) {
+                self.pieces.push(piece);
+            }
+        }
+        Ok(Node::Mixed)
+    }
+    fn spend(&mut self, classes: u128) -> Result<(), StrideLimitError> {
+        let classes = usize::try_from(classes).map_err(|_| StrideLimitError)?;
+        self.budget = self.budget.checked_sub(classes)
+            .ok_or(StrideLimitError)?;
+        Ok(())
+    }
+}
+
+// Joins strided ranges, given in ascending order of their starts within each
+// segment, into longer ones where one continues another.
+#[derive(Default)]
+struct Joiner {
+    // Ranges as offsets: start, end and step.
+    ranges: Vec<(u128, u128, u128)>,
+    // The index of each range of more than one value, by the next value it
+    // would take.
+    by_next: HashMap<u128, usize>,
+    // The index of each range of a single value, by that value.
+    singles: HashMap<u128, usize>,
+    // The last range pushed, if it has a single value.
+    last_single: Option<usize>,
+}
+
+impl Joiner {
+    fn push(&mut self, start: u128, end: u128, step: u128) {
+        let single = start == end;
+        // Continue a range that would take the start next.
+        if let Some(&i) = self.by_next.get(&start) {
+            if single || self.ranges[i].2 == step {
+                self.by_next.remove(&start);
+                self.extend(i, end);
+                return;
+            }
+        }
+        // Extend a single value that comes one step before.
+        if !single {
+            if let Some(i) = start.checked_sub(step)
+                .and_then(|before| self.singles.remove(&before)) {
+                self.ranges[i].2 = step;
+                self.extend(i, end);
+                return;
+            }
+        }
+        // Pair two single values.
+        if single {
+            if let Some(i) = self.last_single.take() {
+                let before = self.ranges[i].0;
+                if self.singles.remove(&before).is_some() {
+                    self.ranges[i].2 = start - before;
+                    self.extend(i, end);
+                    return;
+                }
+            }
+        }
+        let i = self.ranges.len();
+        self.ranges.push((start, end, step));
+        if single {
+            self.singles.insert(start, i);
+            self.last_single = Some(i);
+        } else if let Some(next) = end.checked_add(step) {
+            self.by_next.insert(next, i);
+        }
+    }
+    fn extend(&mut self, i: usize, end: u128) {
+        self.ranges[i].1 = end;
+        self.last_single = None;
+        if let Some(next) = end.checked_add(self.ranges[i].2) {
+            self.by_next.insert(next, i);
+        }
+    }
+}
+
+// The smallest prime factor of `x`, if it is small enough to find quickly, or
+// else `x` itself.
+fn smallest_factor(x: u128) -> u128 {
+    (2..=CLASS_LIMIT as u128)
+        .take_while(|&d| d * d <= x)
+        .find(|&d| x.is_multiple_of(d))
+        .unwrap_or(x)
+}
+
+fn gcd(mut x: u128, mut y: u128) -> u128 {
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    x
+}
+
+// The position of a value among the values of its type.
+fn offset<T: Int>(value: T) -> u128 {
+    T::min_value().checked_distance(value).unwrap()
+}
+
+// The value at a position among the values of its type.
+fn from_offset<T: Int>(offset: u128) -> T {
+    let (negative, magnitude) = T::min_value().to_sign_magnitude();
+    let value = if negative {
+        if offset >= magnitude {
+            T::from_sign_magnitude(false, offset - magnitude)
+        } else {
+            T::from_sign_magnitude(true, magnitude - offset)
+        }
+    } else if T::IS_CHAR && offset >= 0xD800 {
+        // Skip the surrogates.
+        T::from_sign_magnitude(false, offset + 0x800)
+    } else {
+        T::from_sign_magnitude(false, offset)
+    };
+    value.unwrap()
+}
+
+// The remainder of a value modulo `modulus`, as by `rem_euclid`. For `char`,
+// this is the remainder of its position.
+fn residue_of<T: Int>(value: T, modulus: u128) -> u128 {
+    if T::IS_CHAR {
+        return offset(value) % modulus;
+    }
+    let (negative, magnitude) = value.to_sign_magnitude();
+    let remainder = magnitude % modulus;
+    if negative && remainder != 0 {
+        modulus - remainder
+    } else {
+        remainder
+    }
+}
+
+#[cfg(test)]
+mod strided_tests {
+    use super::{CongruenceSet, StrideLimitError, StridedRange,
+                uncovered_and_overlapped_strided};
+    use super::super::IntRange::*;
+    use super::super::test_util::Lcg;
+    #[test]
+    fn new_lowers_end_to_last_member() {
+        let range = StridedRange::new(1u8, 254, 4);
+        assert_eq!(range.end(), 253);
+        assert_eq!(range, StridedRange::new(1u8, 253, 4));
+        assert!(range.contains(5));
+        assert!(!range.contains(6));
+        assert!(StridedRange::new(5i8, 4, 2).is_empty());
+        assert_eq!(StridedRange::new(5i8, 4, 2), StridedRange::new(6, 4, 1));
+        assert_eq!(range.to_string(), "1-253 step 4");
+        assert_eq!(StridedRange::new(7u8, 9, 4).to_string(), "7-7");
+    }
+    #[test]
+    fn congruent_uses_euclidean_remainder() {
+        let odd = StridedRange::congruent(Bound(-10i8, 10), 2, 1).unwrap();
+        assert_eq!((odd.start(), odd.end(), odd.step()), (-9, 9, 2));
+        let range = StridedRange::congruent(Full, 4, 3).unwrap();
+        assert_eq!((range.start(), range.end()), (-125i8, 127));
+        assert_eq!(StridedRange::congruent(Bound(5u8, 6), 4, 0), None);
+        assert_eq!(StridedRange::congruent(Bound(6u8, 5), 4, 0), None);
+    }
+    #[test]
+    fn residues_cover_type() {
+        let ranges: Vec<StridedRange<u8>> = (0..4)
+            .map(|residue| StridedRange::congruent(Full, 4, residue).unwrap())
+            .collect();
+        assert_eq!(uncovered_and_overlapped_strided(&ranges),
+                   Ok((vec![], vec![])));
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_strided(&ranges[..3]).unwrap();
+        assert_eq!(uncovered, vec![StridedRange::new(3, 255, 4)]);
+        assert!(overlapped.is_empty());
+    }
+    #[test]
+    fn mixes_plain_and_strided_ranges() {
+        // Even values, odd values below 100, and everything from 200 up.
+        let ranges = [
+            StridedRange::new(0u8, 255, 2),
+            StridedRange::new(1, 99, 2),
+            StridedRange::from(From(200u8)),
+            ];
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_strided(ranges).unwrap();
+        assert_eq!(uncovered, vec![StridedRange::new(101, 199, 2)]);
+        assert_eq!(overlapped, vec![StridedRange::new(200, 254, 2)]);
+        let set = CongruenceSet::new(ranges).unwrap();
+        assert!(set.contains(99));
+        assert!(!set.contains(101));
+        assert!(set.contains(201));
+        assert!(!set.is_full());
+        assert_eq!(set.complement().unwrap().ranges(), uncovered);
+    }
+    #[test]
+    fn joins_ranges_across_segments() {
+        // The cut at 50 splits the multiples of 3 in two, and the plain
+        // ranges split into segments join up again.
+        let ranges = [
+            StridedRange::new(0u8, 49, 3),
+            StridedRange::new(51, 255, 3),
+            StridedRange::new(0, 9, 1),
+            StridedRange::new(10, 255, 1),
+            ];
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_strided(ranges).unwrap();
+        assert!(uncovered.is_empty());
+        assert_eq!(overlapped, vec![StridedRange::new(0, 255, 3)]);
+        let set = CongruenceSet::new(&ranges[..2]).unwrap();
+        assert_eq!(set.ranges(), vec![StridedRange::new(0, 255, 3)]);
+        let set = CongruenceSet::new(&ranges[2..]).unwrap();
+        assert!(set.is_full());
+        assert_eq!(set.ranges(), vec![StridedRange::from(Full)]);
+    }
+    #[test]
+    fn char_steps_skip_surrogates() {
+        let range = StridedRange::new('\u{D7FE}', '\u{E001}', 2);
+        assert!(range.contains('\u{E000}'));
+        assert!(!range.contains('\u{E001}'));
+        let set = CongruenceSet::new([range]).unwrap();
+        assert_eq!(set.ranges(), vec![StridedRange::new('\u{D7FE}',
+                                                         '\u{E000}', 2)]);
+        assert!(set.complement().unwrap().contains('\u{D7FF}'));
+        assert!(!set.complement().unwrap().contains('\u{E000}'));
+    }
+    #[test]
+    fn matches_brute_force() {
+        let mut lcg = Lcg::new(98765);
+        let mut next = || lcg.next_u16() as i8;
+        for _ in 0..20 {
+            let ranges: Vec<StridedRange<i8>> = (0..6).map(|_| {
+                let (a, b, step) = (next(), next(), next() as u8 % 6 + 1);
+                StridedRange::new(a.min(b), a.max(b), step as u128)
+            }).collect();
+            let (uncovered, overlapped) =
+                uncovered_and_overlapped_strided(&ranges).unwrap();
+            let covered_by = |x: i8| {
+                ranges.iter().filter(|range| range.contains(x)).count()
+            };
+            let contained = |list: &[StridedRange<i8>], x: i8| {
+                list.iter().filter(|range| range.contains(x)).count()
+            };
+            for x in i8::MIN..=i8::MAX {
+                let count = covered_by(x);
+                assert_eq!(contained(&uncovered, x), (count == 0) as usize);
+                assert_eq!(contained(&overlapped, x), (count > 1) as usize);
+            }
+            let set = CongruenceSet::new(&ranges).unwrap();
+            let complement = set.complement().unwrap();
+            for x in i8::MIN..=i8::MAX {
+                assert_eq!(set.contains(x), covered_by(x) > 0);
+                assert_eq!(complement.contains(x), covered_by(x) == 0);
+            }
+        }
+    }
+    #[test]
+    fn large_steps_stay_small() {
+        // Below the last multiple of 2^40, the other values are split by
+        // their lowest set bit, without going through each residue.
+        let range = StridedRange::new(0u64, u64::MAX, 1 << 40);
+        let (uncovered, overlapped) =
+            uncovered_and_overlapped_strided([range]).unwrap();
+        assert!(overlapped.is_empty());
+        assert_eq!(uncovered.len(), 41);
+        assert_eq!(uncovered[0], StridedRange::new(1, range.end(), 2));
+        assert_eq!(uncovered[39], StridedRange::new(1 << 39, range.end(),
+                                                    1 << 40));
+        assert_eq!(uncovered[40],
+                   StridedRange::from(From(range.end() + 1)));
+        let set = CongruenceSet::new([range]).unwrap();
+        assert_eq!(set.ranges(), vec![range]);
+        assert_eq!(set.complement().unwrap().ranges(), uncovered);
+    }
+    #[test]
+    fn large_prime_steps() {
+        // Few values are taken one by one, whatever the step.
+        let step = (1 << 61) - 1;
+        let range = StridedRange::new(0u64, u64::MAX, step);
+        let (uncovered, _) =
+            uncovered_and_overlapped_strided([range]).unwrap();
+        assert_eq!(uncovered.len(), 9);
+        let value = step as u64;
+        assert_eq!(uncovered[1],
+                   StridedRange::from(Bound(value + 1, 2 * value - 1)));
+        // Many values would need a class for each residue.
+        let range = StridedRange::new(0u128, u128::MAX, step);
+        assert_eq!(uncovered_and_overlapped_strided([range]),
+                   Err(StrideLimitError));
+        assert_eq!(CongruenceSet::new([range]).unwrap_err(),
+                   StrideLimitError);
+    }
+    #[test]
+    fn interleaved_ranges_merge() {
+        let ranges = [
+            StridedRange::new(0u8, 255, 6),
+            StridedRange::new(3, 255, 6),
+            ];
+        let set = CongruenceSet::new(ranges).unwrap();
+        assert_eq!(set.ranges(), vec![StridedRange::new(0, 255, 3)]);
+        // Three classes modulo 12 merge into one modulo 4, after the cut at
+        // 100 is joined over.
+        let ranges = [
+            StridedRange::new(1u8, 99, 12),
+            StridedRange::new(97, 255, 12),
+            StridedRange::new(5, 255, 12),
+            StridedRange::new(9, 255, 12),
+            ];
+        let set = CongruenceSet::new(ranges).unwrap();
+        assert_eq!(set.ranges(), vec![StridedRange::new(1, 255, 4)]);
+        // Ranges with different extents do not merge.
+        let ranges = [
+            StridedRange::new(0u8, 100, 6),
+            StridedRange::new(3, 255, 6),
+            ];
+        let set = CongruenceSet::new(ranges).unwrap();
+        assert_eq!(set.ranges().len(), 2);
+    }
+}
@@ -0,0 +1,16 @@
+//! Helpers shared by the unit tests.
+
+/// A simple linear congruential generator, so that tests on pseudorandom
+/// input are deterministic.
+pub struct Lcg(u32);
+
+impl Lcg {
+    pub fn new(seed: u32) -> Self {
+        Lcg(seed)
+    }
+    /// Returns the next 16 pseudorandom bits.
+    pub fn next_u16(&mut self) -> u16 {
+        self.0 = self.0.wrapping_mul(1103515245).wrapping_add(12345);
+        (self.0 >> 16) as u16
+    }
+}
@@ -0,0 +1,256 @@
+//! Validation of input ranges, for callers that want malformed ranges to be
+//! reported rather than silently dropped.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{self, RangeBounds};
+
+use super::{Int, IntRange, uncovered_and_overlapped};
+use super::convert::merge_range_from_bounds;
+
+/// How to treat input ranges that contain no values.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Validation {
+    /// Report every inverted or empty range as an error.
+    Strict,
+    /// Drop inverted and empty ranges, as `uncovered_and_overlapped` does.
+    Ignore,
+    /// Swap the bounds of inverted ranges, so that `Bound(5, 1)` is treated
+    /// as `Bound(1, 5)`. Ranges that are still empty are reported as errors.
+    Swap,
+}
+
+/// The reason that an input range is invalid.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InvalidRangeKind {
+    /// The start of the range is above the end.
+    Inverted,
+    /// The bounds are in order, but the range contains no values, like
+    /// `5..5`.
+    Empty,
+}
+
+/// An invalid input range, with its position in the input.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct InvalidRange<T: Int> {
+    pub index: usize,
+    pub start: ops::Bound<T>,
+    pub end: ops::Bound<T>,
+    pub kind: InvalidRangeKind,
+}
+
+impl<T: Int> Display for InvalidRange<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "range {} (", self.index)?;
+        match self.start {
+            ops::Bound::Included(start) => write!(formatter, "{:?}", start)?,
+            // There is no range syntax for an excluded start, so spell it
+            // out.
+            ops::Bound::Excluded(start) =>
+                write!(formatter, "{:?} exclusive", start)?,
+            ops::Bound::Unbounded => (),
+        }
+        match self.end {
+            ops::Bound::Included(end) => write!(formatter, "..={:?}", end)?,
+            ops::Bound::Excluded(end) => write!(formatter, "..{:?}", end)?,
+            ops::Bound::Unbounded => formatter.write_str("..")?,
+        }
+        match self.kind {
+            InvalidRangeKind::Inverted =>
+                formatter.write_str(") has its bounds reversed"),
+            InvalidRangeKind::Empty => formatter.write_str(") is empty"),
+        }
+    }
+}
+
+/// Error listing every invalid range in an input.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ValidationError<T: Int> {
+    /// The invalid ranges, in input order.
+    pub invalid: Vec<InvalidRange<T>>,
+}
+
+impl<T: Int> Display for ValidationError<T> {
+    fn fmt(&self, formatter: &mut Formatter) -> Result<(), fmt::Error> {
+        let mut first = true;
+        for invalid in self.invalid.iter() {
+            if !first {
+                formatter.write_str("; ")?;
+            }
+            first = false;
+            write!(formatter, "{}", invalid)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Int> Error for ValidationError<T> {}
+
+/// Checks input ranges according to `validation`, and converts the valid
+/// ones to `IntRange`s.
+///
+/// On success, the output has one range for each input range, except that
+/// ranges dropped by `Validation::Ignore` are left out. On failure, every
+/// invalid input range is listed in the error.
+pub fn validate_ranges<T, I>(ranges: I, validation: Validation)
+      -> Result<Vec<IntRange<T>>, ValidationError<T>>
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
+    let mut valid = Vec::new();
+    let mut invalid = Vec::new();
+    for (index, range) in ranges.into_iter().enumerate() {
+        let start = range.start_bound().cloned();
+        let end = range.end_bound().cloned();
+        let kind = match classify(&(start, end)) {
+            Ok(int_range) => {
+                valid.push(int_range);
+                continue;
+            },
+            Err(kind) => kind,
+        };
+        match (validation, kind) {
+            (Validation::Ignore, _) => (),
+            (Validation::Swap, InvalidRangeKind::Inverted) => {
+                let swapped = (swap_value(start, end), swap_value(end, start));
+                match classify(&swapped) {
+                    Ok(int_range) => valid.push(int_range),
+                    Err(kind) => invalid.push(
+                        InvalidRange{index, start, end, kind}),
+                }
+            },
+            _ => invalid.push(InvalidRange{index, start, end, kind}),
+        }
+    }
+    if invalid.is_empty() {
+        Ok(valid)
+    } else {
+        Err(ValidationError{invalid})
+    }
+}
+
+/// Like `uncovered_and_overlapped`, but validates the input first. See
+/// `validate_ranges`.
+#[allow(clippy::type_complexity)]
+pub fn checked_uncovered_and_overlapped<T, I>(ranges: I,
+                                              validation: Validation)
+      -> Result<(Vec<IntRange<T>>, Vec<IntRange<T>>), ValidationError<T>>
+    where T: Int, I: IntoIterator, I::Item: RangeBounds<T> {
+    let valid = validate_ranges(ranges, validation)?;
+    Ok(uncovered_and_overlapped(valid))
+}
+
+// Convert a pair of bounds to an `IntRange`, or say why that's impossible.
+fn classify<T: Int>(bounds: &(ops::Bound<T>, ops::Bound<T>))
+      -> Result<IntRange<T>, InvalidRangeKind> {
+    match merge_range_from_bounds(bounds) {
+        Some(merge_range) => Ok(IntRange::from_merge_range(merge_range)),
+        None => match *bounds {
+            (ops::Bound::Included(start), ops::Bound::Included(end)) |
+            (ops::Bound::Included(start), ops::Bound::Excluded(end)) |
+            (ops::Bound::Excluded(start), ops::Bound::Included(end)) |
+            (ops::Bound::Excluded(start), ops::Bound::Excluded(end))
+                if start > end => Err(InvalidRangeKind::Inverted),
+            _ => Err(InvalidRangeKind::Empty),
+        },
+    }
+}
+
+// Put the value of one bound into the shape of another, so that `10..2`
+// becomes `2..10` when the bounds are swapped.
+fn swap_value<T: Int>(shape: ops::Bound<T>, value: ops::Bound<T>)
+      -> ops::Bound<T> {
+    let value = match value {
+        ops::Bound::Included(x) | ops::Bound::Excluded(x) => x,
+        ops::Bound::Unbounded => return ops::Bound::Unbounded,
+    };
+    match shape {
+        ops::Bound::Included(_) => ops::Bound::Included(value),
+        ops::Bound::Excluded(_) => ops::Bound::Excluded(value),
+        ops::Bound::Unbounded => ops::Bound::Unbounded,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::reversed_empty_ranges, clippy::single_range_in_vec_init)]
+mod validate_tests {
+    use std::ops::Bound::*;
+    use super::{InvalidRange, InvalidRangeKind, Validation, ValidationError};
+    use super::{checked_uncovered_and_overlapped, validate_ranges};
+    use super::super::IntRange;
+    #[test]
+    fn valid_ranges_pass_strict() {
+        assert_eq!(validate_ranges([IntRange::Bound(1u8, 5),
+                                     IntRange::To(0)], Validation::Strict),
+                   Ok(vec![IntRange::Bound(1u8, 5), IntRange::To(0)]));
+    }
+    #[test]
+    fn strict_lists_every_invalid_range() {
+        let ranges = [
+            IntRange::Bound(5u8, 1),
+            IntRange::To(3),
+            IntRange::Bound(9u8, 8),
+            ];
+        assert_eq!(validate_ranges(ranges, Validation::Strict),
+                   Err(ValidationError{invalid: vec![
+                       InvalidRange{index: 0, start: Included(5u8),
+                                    end: Included(1u8),
+                                    kind: InvalidRangeKind::Inverted},
+                       InvalidRange{index: 2, start: Included(9u8),
+                                    end: Included(8u8),
+                                    kind: InvalidRangeKind::Inverted},
+                       ]}));
+    }
+    #[test]
+    fn strict_reports_empty_half_open() {
+        let error = validate_ranges([3i32..3, 0..1], Validation::Strict)
+            .unwrap_err();
+        assert_eq!(error.invalid, vec![
+            InvalidRange{index: 0, start: Included(3i32), end: Excluded(3i32),
+                         kind: InvalidRangeKind::Empty},
+            ]);
+        let error = validate_ranges([..i32::MIN], Validation::Strict)
+            .unwrap_err();
+        assert_eq!(error.invalid[0].kind, InvalidRangeKind::Empty);
+    }
+    #[test]
+    fn ignore_drops_invalid_ranges() {
+        assert_eq!(validate_ranges([IntRange::Bound(5u8, 1), IntRange::To(3)],
+                                   Validation::Ignore),
+                   Ok(vec![IntRange::To(3u8)]));
+    }
+    #[test]
+    fn swap_reverses_inverted_ranges() {
+        assert_eq!(validate_ranges([IntRange::Bound(5u8, 1)],
+                                   Validation::Swap),
+                   Ok(vec![IntRange::Bound(1u8, 5)]));
+        // Half-open ranges keep their shape.
+        assert_eq!(validate_ranges([10i8..2], Validation::Swap),
+                   Ok(vec![IntRange::Bound(2i8, 9)]));
+    }
+    #[test]
+    fn swap_still_reports_empty_ranges() {
+        let error = validate_ranges([4u16..4], Validation::Swap).unwrap_err();
+        assert_eq!(error.invalid[0].kind, InvalidRangeKind::Empty);
+    }
+    #[test]
+    fn checked_entry_point_checks_valid_input() {
+        assert_eq!(checked_uncovered_and_overlapped(
+            [IntRange::Bound(0i8, 5), IntRange::From(3)], Validation::Strict),
+                   Ok((vec![IntRange::To(-1i8)],
+                       vec![IntRange::Bound(3i8, 5)])));
+        assert!(checked_uncovered_and_overlapped(
+            [IntRange::Bound(5i8, 0)], Validation::Strict).is_err());
+    }
+    #[test]
+    fn display_error() {
+        let error = validate_ranges([IntRange::Bound(5u8, 1),
+                                      IntRange::Bound(2, 3)],
+                                    Validation::Strict).unwrap_err();
+        assert_eq!(format!("{}", error),
+                   "range 0 (5..=1) has its bounds reversed");
+        let error = validate_ranges([3i32..3, 4..0], Validation::Strict)
+            .unwrap_err();
+        assert_eq!(format!("{}", error),
+                   "range 0 (3..3) is empty; \
+                    range 1 (4..0) has its bounds reversed");
+    }
+}
@@ -0,0 +1,116 @@
+//! Tests of the `int_range_check` command-line tool.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Run the tool with the given arguments and standard input, returning the
+// exit status and standard output.
+fn run(args: &[&str], input: &str) -> (i32, String) {
+    let (status, output, _) = run_with_errors(args, input);
+    (status, output)
+}
+
+// Run the tool, also returning its standard error.
+fn run_with_errors(args: &[&str], input: &str) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_int_range_check"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap(),
+     String::from_utf8(output.stderr).unwrap())
+}
+
+// Write a file of ranges for the tool to read, returning its path.
+fn write_input(name: &str, contents: &str) -> String {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn lines_continue_after_separators() {
+    let expected = (0, "-: uncovered: 0 | 5..=u8::MAX\n".to_string());
+    assert_eq!(run(&["u8"], "1-2,\n3-4\n"), expected);
+    assert_eq!(run(&["u8"], "1-2 |\n3-4"), expected);
+    assert_eq!(run(&["u8"], "[\n1-2,\n3-4\n]"), expected);
+    assert_eq!(run(&["u8"], "1..=2\n| 3..=4\n"), expected);
+    // A line without a separator starts a new range.
+    assert_eq!(run(&["u8"], "1-2\n\n3-4\n"), expected);
+    // A separator may also follow the last range.
+    assert_eq!(run(&["u8"], "1-2,\n3-4,\n"), expected);
+    assert_eq!(run(&["u8"], "[\n1-2 |\n3-4 |\n]"), expected);
+}
+
+#[test]
+fn exit_status_follows_requirements() {
+    assert_eq!(run(&["i8", "--require-exhaustive", "--require-disjoint"],
+                   "..=-1\n0..\n"),
+               (0, "-: exhaustive and disjoint\n".to_string()));
+    assert_eq!(run(&["u8", "--require-disjoint"], "0-9, 20-29").0, 0);
+    assert_eq!(run(&["u8", "--require-exhaustive"], "0-9, 20-29"),
+               (1, "-: uncovered: 10..=19 | 30..=u8::MAX\n".to_string()));
+    assert_eq!(run(&["u8", "--require-disjoint"], "0-9, 5-255"),
+               (1, "-: ranges 0, 1 overlap on 5..=9\n".to_string()));
+}
+
+#[test]
+fn errors_exit_with_status_2() {
+    assert_eq!(run(&["u8"], "1-x"), (2, String::new()));
+    assert_eq!(run(&["u8"], "9-1"), (2, String::new()));
+    assert_eq!(run(&["u8"], "0-300"), (2, String::new()));
+    assert_eq!(run(&["f32"], "0-9"), (2, String::new()));
+    assert_eq!(run(&["u8", "--exhaustive"], "0-9"), (2, String::new()));
+    assert_eq!(run(&["u8", "/nonexistent/ranges.txt"], ""),
+               (2, String::new()));
+}
+
+#[test]
+fn errors_at_end_of_input_say_so() {
+    let (status, _, errors) = run_with_errors(&["u8"], "1-2,\n3-");
+    assert_eq!(status, 2);
+    assert_eq!(errors,
+               "-: expected number at position 7 (at end of input)\n");
+}
+
+#[test]
+fn other_files_are_checked_after_an_error() {
+    let good = write_input("good.txt", "..=9\n10..\n");
+    let bad = write_input("bad.txt", "1-x\n");
+    let (status, output, errors) =
+        run_with_errors(&["u8", &bad, &good, "/nonexistent/ranges.txt"], "");
+    assert_eq!(status, 2);
+    assert_eq!(output, format!("{}: exhaustive and disjoint\n", good));
+    assert_eq!(errors.lines().count(), 2);
+    assert!(errors.starts_with(&format!("{}: ", bad)));
+    let (status, output) = run(&["u8", "--json", &bad, &good], "");
+    assert_eq!(status, 2);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json[0]["input"], good.as_str());
+    assert_eq!(json.as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn json_reports_each_input() {
+    let (status, output) = run(&["i8", "--json"], "0-9,\n5-20\n");
+    assert_eq!(status, 0);
+    let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(json, serde_json::json!([{
+        "input": "-",
+        "type": "i8",
+        "exhaustive": false,
+        "disjoint": false,
+        "uncovered": [
+            {"start": null, "end": -1},
+            {"start": 21, "end": null},
+        ],
+        "overlapped": [{"start": 5, "end": 9}],
+        "provenance": [{"range": {"start": 5, "end": 9}, "sources": [0, 1]}],
+    }]));
+}